@@ -0,0 +1,459 @@
+use ff::PrimeField;
+use halo2_proofs::halo2curves::pasta::Fp;
+use serde::{Deserialize, Serialize};
+
+use crate::circuits::simple_spirals::{
+    INSTANCE_BACKGROUND_TYPE, INSTANCE_NUM_ARMS, INSTANCE_SPIRAL_TYPE, INSTANCE_VARIANT_ID,
+    PUBLIC_INPUT_NAMES,
+};
+use crate::config::{MAX_CANVAS_SIZE, MIN_CANVAS_SIZE};
+use crate::contract::{
+    add_triangle_batch_calldata, initialize_galaxy_calldata, keccak256, mint_spiral_calldata,
+    pack_triangles, packed_triangles_hash, triangle_batches, ADD_TRIANGLE_BATCH_SIGNATURE,
+    DEFAULT_TRIANGLE_OPACITY, INITIALIZE_GALAXY_SIGNATURE, MINT_SPIRAL_SIGNATURE,
+};
+use crate::generation::{Galaxy, PARTICLES_PER_ARM};
+use crate::prover::SpiralProof;
+use crate::render::background_name;
+
+// Canvas used when a request leaves it out (or sends 0)
+pub const DEFAULT_CANVAS_SIZE: u64 = 420;
+
+#[derive(Serialize, Deserialize)]
+pub struct SpiralRequest {
+    pub seed: u64,
+    pub canvas_size: u64,
+}
+
+#[derive(Serialize)]
+pub struct SpiralResponse {
+    pub seed: u64,
+    pub variant: u64,
+    pub spiral_type: u64,
+    pub num_arms: u64,
+    pub background_type: u64, // Background type (0-20)
+    pub particles: Vec<ParticleData>,
+    pub triangles: Vec<TriangleData>,
+    pub config: ConfigData,
+    // Galaxy-specific metadata
+    pub galaxy_stats: GalaxyStats,
+    // 0x-prefixed addTriangleBatch bytes: 10 bytes per triangle (x1..y3, arm, index, type, opacity)
+    pub packed_bytes: String,
+}
+
+#[derive(Serialize)]
+pub struct ParticleData {
+    pub x: u64,
+    pub y: u64,
+    pub arm_index: u64,
+    pub particle_index: u64,
+}
+
+#[derive(Serialize)]
+pub struct TriangleData {
+    pub vertices: [(u64, u64); 3],
+    pub arm_index: u64,
+    pub triangle_index: u64,
+    pub triangle_type: u64, // 0=spiral particle, 1=star, 2=core, 3=dust
+    pub size: u64,          // Triangle size for micro-triangle rendering
+    // Add scaled coordinates for uint8 packing
+    pub packed_vertices: [(u8, u8); 3], // Scaled to 0-255 for smart contract
+}
+
+#[derive(Serialize)]
+pub struct ConfigData {
+    pub spiral_type: u64,
+    pub num_arms: u64,
+    pub background_type: u64, // Background type (0-20)
+    pub spiral_quotient: u64,
+    pub arms_quotient: u64,
+    pub arms_remainder: u64,
+}
+
+// Optimized galaxy-specific statistics
+#[derive(Serialize)]
+pub struct GalaxyStats {
+    pub particles_per_arm: u64,
+    pub total_particles: u64,
+    pub micro_triangles: u64,
+    pub galaxy_type: String,
+    pub background_type: String,
+    pub density_factor: f64,      // Relative to original (now 23/69)
+    pub gas_savings_estimate: u64, // Estimated gas savings
+}
+
+// SVG Export request
+#[derive(Serialize, Deserialize)]
+pub struct SVGExportRequest {
+    pub seed: u64,
+    pub canvas_size: Option<u64>,
+    pub include_background: Option<bool>,
+}
+
+// PNG export request: same fields as the SVG export, answered with image/png bytes
+#[derive(Serialize, Deserialize)]
+pub struct PngExportRequest {
+    pub seed: u64,
+    pub canvas_size: Option<u64>,
+    pub include_background: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct SVGExportResponse {
+    pub svg_content: String,
+    pub spiral_data: SpiralResponse,
+    pub export_info: SVGExportInfo,
+}
+
+#[derive(Serialize)]
+pub struct SVGExportInfo {
+    pub canvas_size: u64,
+    pub triangle_count: u64,
+    pub background_included: bool,
+    pub file_size_bytes: usize,
+    pub export_timestamp: u64,
+}
+
+// Gas estimation request (both fields optional, defaults to seed 12345 on a 420px canvas)
+#[derive(Serialize, Deserialize)]
+pub struct GasRequest {
+    pub seed: Option<u64>,
+    pub canvas_size: Option<u64>,
+}
+
+// Proof request (canvas defaults to 420px)
+#[derive(Serialize, Deserialize)]
+pub struct ProveRequest {
+    pub seed: u64,
+    pub canvas_size: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ProveResponse {
+    pub seed: u64,
+    pub canvas_size: u64,
+    pub num_arms: u64,
+    pub k: u32,
+    pub proof: String, // Hex-encoded transcript bytes
+    pub proof_size_bytes: usize,
+    pub public_inputs: Vec<PublicInputData>,
+    pub proving_time_ms: u64,
+    pub key_cached: bool, // false when this request paid for keygen
+    pub packed_triangles_hash: String, // keccak256 of the contract's packed triangle bytes
+}
+
+#[derive(Serialize)]
+pub struct PublicInputData {
+    pub name: String,
+    pub value: String, // 0x-prefixed big-endian field element
+}
+
+// Verification request: a proof plus what the caller claims it proves.
+// Every claimed field is optional; the seed alone fixes the expected public inputs.
+#[derive(Serialize, Deserialize)]
+pub struct VerifyRequest {
+    pub proof: String, // Hex-encoded, as returned by /api/prove
+    pub seed: u64,
+    pub canvas_size: Option<u64>,
+    pub variant_id: Option<u64>,
+    pub spiral_type: Option<u64>,
+    pub num_arms: Option<u64>,
+    pub background_type: Option<u64>,
+    pub public_inputs: Option<Vec<String>>, // 0x-prefixed, in PUBLIC_INPUT_NAMES order
+}
+
+#[derive(Serialize)]
+pub struct VerifyResponse {
+    pub valid: bool,
+    pub proof_verified: bool, // Proof checks out against the seed's expected public inputs
+    pub mismatched_inputs: Vec<PublicInputMismatch>,
+    pub reason: Option<String>,
+    pub expected_public_inputs: Vec<PublicInputData>,
+}
+
+#[derive(Serialize)]
+pub struct PublicInputMismatch {
+    pub name: String,
+    pub claimed: String,
+    pub expected: String,
+}
+
+// Contract calldata request. token_id is only known once initializeGalaxy is mined,
+// so batch calldata is left out until the caller supplies it.
+#[derive(Serialize, Deserialize)]
+pub struct CalldataRequest {
+    pub seed: u64,
+    pub canvas_size: Option<u64>,
+    pub to: String,                    // Recipient address (0x + 40 hex)
+    pub token_id: Option<u64>,
+    pub zk_proof_hash: Option<String>, // Defaults to keccak256 of the packed triangles
+    pub token_uri: Option<String>,
+    pub batch_size: Option<usize>,     // Triangles per addTriangleBatch, capped at MAX_TRIANGLES_PER_BATCH
+}
+
+#[derive(Serialize)]
+pub struct CalldataResponse {
+    pub seed: u64,
+    pub expected_triangles: u64,
+    pub zk_proof_hash: String,
+    pub initialize_galaxy: EncodedCall,
+    pub add_triangle_batches: Option<Vec<EncodedCall>>,
+    pub mint_spiral: EncodedCall, // Single-transaction alternative to initialize + batches
+}
+
+#[derive(Serialize)]
+pub struct EncodedCall {
+    pub function: String,
+    pub data: String, // 0x-prefixed calldata, selector included
+}
+
+#[derive(Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+// Gas simulation request (batch_size defaults to MAX_TRIANGLES_PER_BATCH)
+#[derive(Serialize, Deserialize)]
+pub struct GasSimulationRequest {
+    pub seed: u64,
+    pub canvas_size: Option<u64>,
+    pub batch_size: Option<usize>,
+}
+
+// The requested canvas size, or the server's configured default for a missing or 0 size.
+// Requests get the range the config's default is held to: smaller canvases can't be
+// proven, larger ones overflow the spiral math.
+pub fn canvas_size_or(canvas_size: Option<u64>, default: u64) -> Result<u64, String> {
+    let canvas_size = canvas_size.filter(|&size| size > 0).unwrap_or(default);
+    if !(MIN_CANVAS_SIZE..=MAX_CANVAS_SIZE).contains(&canvas_size) {
+        return Err(format!("canvas_size {} is outside {}..={}", canvas_size, MIN_CANVAS_SIZE, MAX_CANVAS_SIZE));
+    }
+    Ok(canvas_size)
+}
+
+// Helper function to scale coordinates to uint8 range (0-255)
+pub fn scale_to_uint8(coord: u64, canvas_size: u64) -> u8 {
+    ((coord * 255) / canvas_size).min(255) as u8
+}
+
+// Helper function to scale uint8 back to canvas coordinates
+pub fn scale_from_uint8(coord: u8, canvas_size: u64) -> u64 {
+    ((coord as u64 * canvas_size) / 255).min(canvas_size)
+}
+
+pub fn galaxy_type_name(spiral_type: u64) -> &'static str {
+    match spiral_type {
+        0 => "Tight Galaxy",
+        1 => "Loose Galaxy",
+        _ => "Classic Galaxy",
+    }
+}
+
+// JSON shape served by /api/generate (and embedded in the export responses)
+pub fn spiral_response(galaxy: &Galaxy) -> SpiralResponse {
+    let canvas_size = galaxy.canvas_size;
+    let particles_per_arm = PARTICLES_PER_ARM;
+
+    let particles: Vec<ParticleData> = galaxy.particles.iter()
+        .map(|particle| ParticleData {
+            x: particle.x,
+            y: particle.y,
+            arm_index: particle.arm_index,
+            particle_index: particle.particle_index,
+        })
+        .collect();
+
+    let triangles: Vec<TriangleData> = galaxy.triangles.iter()
+        .map(|triangle| TriangleData {
+            vertices: triangle.vertices,
+            arm_index: triangle.arm_index,
+            triangle_index: triangle.triangle_index,
+            triangle_type: triangle.triangle_type,
+            size: triangle.size,
+            // Create packed coordinates for smart contract (uint8)
+            packed_vertices: triangle.vertices.map(|(x, y)| {
+                (scale_to_uint8(x, canvas_size), scale_to_uint8(y, canvas_size))
+            }),
+        })
+        .collect();
+
+    let config = ConfigData {
+        spiral_type: galaxy.spiral_type,
+        num_arms: galaxy.num_arms,
+        background_type: galaxy.background_type,
+        spiral_quotient: galaxy.spiral_quotient,
+        arms_quotient: galaxy.arms_quotient,
+        arms_remainder: galaxy.arms_remainder,
+    };
+
+    // OPTIMIZED: Galaxy statistics with gas savings
+    let galaxy_stats = GalaxyStats {
+        particles_per_arm,
+        total_particles: galaxy.num_arms * particles_per_arm,
+        micro_triangles: triangles.len() as u64,
+        galaxy_type: galaxy_type_name(galaxy.spiral_type).to_string(),
+        background_type: background_name(galaxy.background_type).to_string(),
+        density_factor: particles_per_arm as f64 / 69.0, // Show reduction from original
+        gas_savings_estimate: 10_400_000, // Estimated 10.4M gas savings
+    };
+
+    let packed_bytes = format!("0x{}", hex::encode(pack_triangles(&triangles, DEFAULT_TRIANGLE_OPACITY)));
+
+    SpiralResponse {
+        seed: galaxy.seed,
+        variant: galaxy.variant,
+        spiral_type: galaxy.spiral_type,
+        num_arms: galaxy.num_arms,
+        background_type: galaxy.background_type,
+        particles,
+        triangles,
+        config,
+        galaxy_stats,
+        packed_bytes,
+    }
+}
+
+// Field elements are shown big-endian so small values read naturally (0x...0007)
+pub fn field_to_hex(value: &Fp) -> String {
+    let mut bytes = value.to_repr();
+    bytes.reverse();
+    format!("0x{}", hex::encode(bytes))
+}
+
+// Inverse of field_to_hex; None for malformed or non-canonical values
+pub fn hex_to_field(value: &str) -> Option<Fp> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.is_empty() || digits.len() > 64 {
+        return None;
+    }
+    let mut bytes = hex::decode(format!("{:0>64}", digits)).ok()?;
+    bytes.reverse();
+
+    let repr: [u8; 32] = bytes.try_into().ok()?;
+    Option::from(Fp::from_repr(repr))
+}
+
+pub fn public_input_data(public_inputs: &[Fp]) -> Vec<PublicInputData> {
+    PUBLIC_INPUT_NAMES.iter()
+        .zip(public_inputs)
+        .map(|(name, value)| PublicInputData {
+            name: name.to_string(),
+            value: field_to_hex(value),
+        })
+        .collect()
+}
+
+// Compare a request's claims against the public inputs its seed actually produces
+pub fn public_input_mismatches(request: &VerifyRequest, expected: &[Fp]) -> Result<Vec<PublicInputMismatch>, String> {
+    let mut claimed: Vec<Option<Fp>> = vec![None; PUBLIC_INPUT_NAMES.len()];
+
+    if let Some(values) = &request.public_inputs {
+        if values.len() != PUBLIC_INPUT_NAMES.len() {
+            return Err(format!(
+                "expected {} public inputs, got {}",
+                PUBLIC_INPUT_NAMES.len(),
+                values.len()
+            ));
+        }
+        for (slot, value) in claimed.iter_mut().zip(values) {
+            *slot = Some(hex_to_field(value).ok_or_else(|| format!("invalid field element: {}", value))?);
+        }
+    }
+
+    // Plain config claims take precedence over the matching raw public input
+    let config_claims = [
+        (INSTANCE_VARIANT_ID, request.variant_id),
+        (INSTANCE_SPIRAL_TYPE, request.spiral_type),
+        (INSTANCE_NUM_ARMS, request.num_arms),
+        (INSTANCE_BACKGROUND_TYPE, request.background_type),
+    ];
+    for (index, value) in config_claims.iter().filter_map(|&(index, value)| Some((index, value?))) {
+        claimed[index] = Some(Fp::from(value));
+    }
+
+    Ok(claimed.iter()
+        .zip(expected)
+        .zip(PUBLIC_INPUT_NAMES)
+        .filter_map(|((claimed, expected), name)| match claimed {
+            Some(value) if value != expected => Some(PublicInputMismatch {
+                name: name.to_string(),
+                claimed: field_to_hex(value),
+                expected: field_to_hex(expected),
+            }),
+            _ => None,
+        })
+        .collect())
+}
+
+pub fn prove_response(proof: &SpiralProof, galaxy: &Galaxy, proving_time_ms: u64, key_cached: bool) -> ProveResponse {
+    let public_inputs = public_input_data(&proof.public_inputs);
+    let triangles = spiral_response(galaxy).triangles;
+    let packed_triangles_hash = packed_triangles_hash(&triangles, DEFAULT_TRIANGLE_OPACITY);
+
+    ProveResponse {
+        seed: proof.seed,
+        canvas_size: proof.canvas_size,
+        num_arms: galaxy.num_arms,
+        k: proof.k,
+        proof: hex::encode(&proof.proof),
+        proof_size_bytes: proof.proof.len(),
+        public_inputs,
+        proving_time_ms,
+        key_cached,
+        packed_triangles_hash: format!("0x{}", hex::encode(packed_triangles_hash)),
+    }
+}
+
+fn encoded_call(function: &str, data: &[u8]) -> EncodedCall {
+    EncodedCall {
+        function: function.to_string(),
+        data: format!("0x{}", hex::encode(data)),
+    }
+}
+
+pub fn calldata_response(
+    galaxy: &Galaxy,
+    to: [u8; 20],
+    token_id: Option<u64>,
+    zk_proof_hash: Option<[u8; 32]>,
+    token_uri: &str,
+    batch_size: usize,
+) -> CalldataResponse {
+    let triangles = spiral_response(galaxy).triangles;
+    let packed = pack_triangles(&triangles, DEFAULT_TRIANGLE_OPACITY);
+    let zk_proof_hash = zk_proof_hash.unwrap_or_else(|| keccak256(&packed));
+    let expected_triangles = triangles.len() as u64;
+
+    let add_triangle_batches = token_id.map(|token_id| {
+        let batches = triangle_batches(&packed, batch_size);
+        let last = batches.len().saturating_sub(1);
+        batches.iter()
+            .enumerate()
+            .map(|(i, batch)| {
+                encoded_call(ADD_TRIANGLE_BATCH_SIGNATURE, &add_triangle_batch_calldata(token_id, batch, i == last))
+            })
+            .collect()
+    });
+
+    CalldataResponse {
+        seed: galaxy.seed,
+        expected_triangles,
+        zk_proof_hash: format!("0x{}", hex::encode(zk_proof_hash)),
+        initialize_galaxy: encoded_call(
+            INITIALIZE_GALAXY_SIGNATURE,
+            &initialize_galaxy_calldata(to, galaxy, zk_proof_hash, token_uri, expected_triangles),
+        ),
+        add_triangle_batches,
+        mint_spiral: encoded_call(
+            MINT_SPIRAL_SIGNATURE,
+            &mint_spiral_calldata(to, galaxy, &packed, zk_proof_hash, token_uri),
+        ),
+    }
+}
+
+pub fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
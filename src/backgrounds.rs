@@ -0,0 +1,224 @@
+// The 21 background types as plain data: a base fill plus overlay features.
+// Everything is integers (colors as hex strings, opacity in percent, geometry in
+// reference-frame units) so the same table can be ported to the contract.
+
+// Geometry below is laid out on the contract's 420px canvas
+pub const REFERENCE_FRAME: u64 = 420;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseFill {
+    Solid(&'static str),
+    // Stops as (offset %, color), centred on the canvas
+    Radial(&'static [(u8, &'static str)]),
+    // Stops as (offset %, color), top-left to bottom-right
+    Linear(&'static [(u8, &'static str)]),
+}
+
+// Overlays, drawn in order on top of the base fill. Positions marked "seeded" come from
+// SeedRng, so two galaxies with the same background still differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    // Filled circle at the centre
+    Disc { radius: u64, color: &'static str, opacity: u8 },
+    // Stroked circles at the centre
+    Rings { radii: &'static [u64], width: u64, color: &'static str, opacity: u8 },
+    // Lines out of the centre, evenly spaced from a seeded start angle (count divides 32)
+    Rays { count: u64, length: u64, width: u64, color: &'static str, opacity: u8 },
+    // Parallel lines at one seeded angle from seeded start points
+    Streaks { count: u64, length: u64, width: u64, color: &'static str, opacity: u8 },
+    // Circles at seeded positions with seeded radii in min..=max
+    Scatter { count: u64, min_radius: u64, max_radius: u64, color: &'static str, opacity: u8 },
+    // Seeded star scatter in star colours (procedural::star_scatter)
+    Starfield { count: u64 },
+    // Seeded clouds of soft puffs, one palette colour per cloud (procedural::nebula_clouds)
+    Nebula { clouds: u64, puffs: u64, palette: &'static [&'static str], opacity: u8 },
+    // Seeded dark band across the canvas (procedural::dust_lane)
+    DustLane { puffs: u64, width: u64, color: &'static str, opacity: u8 },
+    // Horizontal curves with seeded height and sag
+    Bands { count: u64, width: u64, color: &'static str, opacity: u8 },
+    // Seeded nodes, each linked to the next and the one three along
+    Web { nodes: u64, color: &'static str, opacity: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackgroundSpec {
+    pub name: &'static str,
+    pub base: BaseFill,
+    pub features: &'static [Feature],
+}
+
+// Indexed by background_type (see generate_background_type); order matches the contract's BackgroundType enum
+pub const BACKGROUNDS: [BackgroundSpec; 21] = [
+    // 0-4
+    BackgroundSpec {
+        name: "Deep Space",
+        base: BaseFill::Radial(&[(0, "#0a0a2e"), (100, "#1a1a3a")]),
+        features: &[],
+    },
+    BackgroundSpec {
+        name: "Nebula",
+        base: BaseFill::Radial(&[(0, "#2d1b4e"), (50, "#4a2c5a"), (100, "#1a0f2e")]),
+        features: &[
+            Feature::Nebula { clouds: 3, puffs: 8, palette: &["#7b3fa0", "#c2409e", "#3f5fbf"], opacity: 12 },
+            Feature::Starfield { count: 40 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Starfield",
+        base: BaseFill::Solid("#000000"),
+        features: &[Feature::Starfield { count: 120 }],
+    },
+    BackgroundSpec {
+        name: "Cosmic",
+        base: BaseFill::Radial(&[(0, "#2c1810"), (100, "#4a2f1a")]),
+        features: &[
+            Feature::Starfield { count: 60 },
+            Feature::DustLane { puffs: 14, width: 30, color: "#1a0d05", opacity: 35 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Void",
+        base: BaseFill::Solid("#0a0a0a"),
+        features: &[],
+    },
+    // 5-9
+    BackgroundSpec {
+        name: "Aurora",
+        base: BaseFill::Radial(&[(0, "#001a33"), (100, "#003366")]),
+        features: &[Feature::Bands { count: 3, width: 14, color: "#3dff9a", opacity: 25 }],
+    },
+    BackgroundSpec {
+        name: "Galaxy Core",
+        base: BaseFill::Radial(&[(0, "#ffcc00"), (100, "#ff6600")]),
+        features: &[
+            Feature::Disc { radius: 60, color: "#fff4cc", opacity: 60 },
+            Feature::DustLane { puffs: 16, width: 24, color: "#4d1f00", opacity: 30 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Solar Wind",
+        base: BaseFill::Radial(&[(0, "#336699"), (100, "#003366")]),
+        features: &[Feature::Streaks { count: 10, length: 120, width: 1, color: "#cce6ff", opacity: 30 }],
+    },
+    BackgroundSpec {
+        name: "Dark Matter",
+        base: BaseFill::Solid("#1a0d26"),
+        features: &[Feature::Scatter { count: 8, min_radius: 20, max_radius: 50, color: "#2e1a40", opacity: 50 }],
+    },
+    BackgroundSpec {
+        name: "Quasar",
+        base: BaseFill::Radial(&[(0, "#ff3366"), (100, "#990033")]),
+        features: &[
+            Feature::Rays { count: 2, length: 210, width: 4, color: "white", opacity: 50 },
+            Feature::Disc { radius: 12, color: "white", opacity: 90 },
+        ],
+    },
+    // 10-14
+    BackgroundSpec {
+        name: "Pulsar",
+        base: BaseFill::Radial(&[(0, "#0a0f2e"), (100, "#000010")]),
+        features: &[
+            Feature::Rings { radii: &[20, 45, 75, 110], width: 2, color: "#66ccff", opacity: 35 },
+            Feature::Rays { count: 2, length: 200, width: 3, color: "#b3e6ff", opacity: 45 },
+            Feature::Disc { radius: 6, color: "#e6f7ff", opacity: 100 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Black Hole",
+        base: BaseFill::Radial(&[(0, "#000000"), (30, "#000000"), (55, "#3d1f00"), (100, "#120800")]),
+        features: &[
+            Feature::Rings { radii: &[70, 78], width: 4, color: "#ff9933", opacity: 70 },
+            Feature::Disc { radius: 55, color: "#000000", opacity: 100 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Supernova",
+        base: BaseFill::Radial(&[(0, "#fff2cc"), (25, "#ff6633"), (60, "#66001a"), (100, "#0d0005")]),
+        features: &[Feature::Rays { count: 16, length: 200, width: 2, color: "#ffcc66", opacity: 40 }],
+    },
+    BackgroundSpec {
+        name: "Comet Trail",
+        base: BaseFill::Linear(&[(0, "#000814"), (100, "#001d3d")]),
+        features: &[
+            Feature::Scatter { count: 20, min_radius: 1, max_radius: 1, color: "white", opacity: 70 },
+            Feature::Streaks { count: 6, length: 160, width: 2, color: "#bde0fe", opacity: 35 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Asteroid",
+        base: BaseFill::Radial(&[(0, "#1c1c1c"), (100, "#0d0d0d")]),
+        features: &[Feature::Scatter { count: 14, min_radius: 3, max_radius: 14, color: "#5c544d", opacity: 90 }],
+    },
+    // 15-19
+    BackgroundSpec {
+        name: "Plasma Storm",
+        base: BaseFill::Radial(&[(0, "#ff00cc"), (50, "#6600cc"), (100, "#1a0033")]),
+        features: &[Feature::Bands { count: 4, width: 8, color: "#ff66ff", opacity: 30 }],
+    },
+    BackgroundSpec {
+        name: "Ion Cloud",
+        base: BaseFill::Radial(&[(0, "#00ffcc"), (50, "#006680"), (100, "#001a26")]),
+        features: &[Feature::Nebula { clouds: 2, puffs: 6, palette: &["#66ffe0", "#33ccff"], opacity: 12 }],
+    },
+    BackgroundSpec {
+        name: "Magnetosphere",
+        base: BaseFill::Radial(&[(0, "#001433"), (100, "#000814")]),
+        features: &[
+            Feature::Rings { radii: &[60, 100, 140, 180], width: 1, color: "#4da6ff", opacity: 25 },
+            Feature::Bands { count: 2, width: 6, color: "#80bfff", opacity: 20 },
+        ],
+    },
+    BackgroundSpec {
+        name: "Cosmic Web",
+        base: BaseFill::Solid("#05050f"),
+        features: &[Feature::Web { nodes: 16, color: "#6666cc", opacity: 35 }],
+    },
+    BackgroundSpec {
+        name: "Gamma Burst",
+        base: BaseFill::Radial(&[(0, "#ccffcc"), (20, "#33cc33"), (60, "#003300"), (100, "#000a00")]),
+        features: &[
+            Feature::Rays { count: 8, length: 150, width: 1, color: "#99ff99", opacity: 35 },
+            Feature::Rays { count: 2, length: 210, width: 10, color: "#e6ffe6", opacity: 60 },
+        ],
+    },
+    // 20
+    BackgroundSpec {
+        name: "Quantum Foam",
+        base: BaseFill::Radial(&[(0, "#1a0033"), (100, "#330066")]),
+        features: &[Feature::Scatter { count: 30, min_radius: 2, max_radius: 6, color: "#b380ff", opacity: 25 }],
+    },
+];
+
+pub fn background_spec(background_type: u64) -> Option<&'static BackgroundSpec> {
+    BACKGROUNDS.get(background_type as usize)
+}
+
+// splitmix64, one stream per (seed, feature): wrapping integer ops only, so it ports to Solidity `unchecked`
+#[derive(Debug, Clone)]
+pub struct SeedRng {
+    state: u64,
+}
+
+impl SeedRng {
+    pub fn new(seed: u64, stream: u64) -> Self {
+        SeedRng { state: seed ^ stream.wrapping_mul(0xd1b5_4a32_d192_ed03) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform enough for art: 0..n (n > 0)
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    // min..=max
+    pub fn between(&mut self, min: u64, max: u64) -> u64 {
+        min + self.below(max - min + 1)
+    }
+}
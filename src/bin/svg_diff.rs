@@ -0,0 +1,57 @@
+// Compare the server's SVG preview with what zkBgNFT.generateCompleteSVG returns after minting.
+// Usage: cargo run --bin svg_diff -- [seed] [canvas_size]
+use zkbg::api::{canvas_size_or, spiral_response, DEFAULT_CANVAS_SIZE};
+use zkbg::generation::generate_galaxy;
+use zkbg::onchain_render::{diff_svg, render_onchain_svg, SvgDiffLine};
+use zkbg::render::generate_complete_svg;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let seed = args.next().and_then(|s| s.parse().ok()).unwrap_or(12345u64);
+    let canvas_size = match args.next().map(|s| s.parse::<u64>()) {
+        Some(Err(e)) => Err(format!("canvas_size is not a number: {}", e)),
+        Some(Ok(canvas_size)) => canvas_size_or(Some(canvas_size), DEFAULT_CANVAS_SIZE),
+        None => Ok(DEFAULT_CANVAS_SIZE),
+    };
+    let canvas_size = canvas_size.unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    });
+
+    let galaxy = generate_galaxy(seed, canvas_size);
+    let response = spiral_response(&galaxy);
+    let packed = hex::decode(response.packed_bytes.trim_start_matches("0x")).expect("server packs valid hex");
+
+    let offchain = generate_complete_svg(&galaxy, true);
+    let onchain = match render_onchain_svg(seed, galaxy.spiral_type, galaxy.num_arms, &packed) {
+        Ok(svg) => svg,
+        Err(revert) => {
+            println!("❌ generateCompleteSVG reverts for seed {}: {}", seed, revert);
+            std::process::exit(1);
+        }
+    };
+
+    let diff = diff_svg(&offchain, &onchain);
+    let (mut same, mut offchain_only, mut onchain_only) = (0, 0, 0);
+    for line in &diff {
+        match line {
+            SvgDiffLine::Same(_) => same += 1,
+            SvgDiffLine::OffchainOnly(element) => {
+                offchain_only += 1;
+                println!("- {}", element);
+            }
+            SvgDiffLine::OnchainOnly(element) => {
+                onchain_only += 1;
+                println!("+ {}", element);
+            }
+        }
+    }
+
+    println!(
+        "\n🔍 Seed {} ({}px preview): {} elements match, {} preview-only (-), {} on-chain-only (+)",
+        seed, canvas_size, same, offchain_only, onchain_only
+    );
+    if offchain_only + onchain_only == 0 {
+        println!("✅ Preview is byte-identical to the on-chain SVG");
+    }
+}
@@ -0,0 +1,169 @@
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+// Byte-wise running-sum range check backed by a 256-entry lookup table
+pub const RANGE_CHECK_BITS: usize = 8;
+const RANGE_CHECK_BASE: u64 = 1 << RANGE_CHECK_BITS;
+
+#[derive(Debug, Clone)]
+pub struct RangeCheckConfig {
+    pub value: Column<Advice>,       // Copy of the cell being checked
+    pub running_sum: Column<Advice>, // z_0 = value + offset, z_{i+1} = (z_i - byte_i) / 256
+    pub offset: Column<Fixed>,
+    pub table: TableColumn,          // 0..256
+    pub selector_init: Selector,
+    pub selector_byte: Selector,
+    pub selector_end: Selector,
+}
+
+impl RangeCheckConfig {
+    pub fn configure<F: PrimeFieldBits>(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        running_sum: Column<Advice>,
+    ) -> Self {
+        let offset = meta.fixed_column();
+        let table = meta.lookup_table_column();
+        let selector_init = meta.selector();
+        let selector_byte = meta.complex_selector();
+        let selector_end = meta.selector();
+
+        meta.enable_equality(value);
+
+        // z_0 = value + offset
+        meta.create_gate("range_check_init", |meta| {
+            let s = meta.query_selector(selector_init);
+            let value = meta.query_advice(value, Rotation::cur());
+            let z = meta.query_advice(running_sum, Rotation::cur());
+            let offset = meta.query_fixed(offset, Rotation::cur());
+
+            vec![
+                s * (z - (value + offset)),
+            ]
+        });
+
+        // Each step strips one byte: z_i - 256 * z_{i+1} must be in [0, 256)
+        meta.lookup("range_check_byte", |meta| {
+            let s = meta.query_selector(selector_byte);
+            let z_cur = meta.query_advice(running_sum, Rotation::cur());
+            let z_next = meta.query_advice(running_sum, Rotation::next());
+            let base = Expression::Constant(F::from(RANGE_CHECK_BASE));
+
+            vec![(s * (z_cur - z_next * base), table)]
+        });
+
+        // After the last byte nothing may be left over
+        meta.create_gate("range_check_end", |meta| {
+            let s = meta.query_selector(selector_end);
+            let z = meta.query_advice(running_sum, Rotation::cur());
+
+            vec![
+                s * z,
+            ]
+        });
+
+        RangeCheckConfig {
+            value,
+            running_sum,
+            offset,
+            table,
+            selector_init,
+            selector_byte,
+            selector_end,
+        }
+    }
+
+    pub fn load_table<F: PrimeFieldBits>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range check bytes",
+            |mut table| {
+                for byte in 0..RANGE_CHECK_BASE {
+                    table.assign_cell(|| "byte", self.table, byte as usize, || Value::known(F::from(byte)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // Prove value < 256^num_bytes
+    pub fn range_check<F: PrimeFieldBits>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bytes: usize,
+    ) -> Result<(), Error> {
+        self.decompose(layouter, value, 0, num_bytes)
+    }
+
+    // Prove value < bound by checking both value and value + (256^n - bound) fit in n bytes
+    pub fn less_than<F: PrimeFieldBits>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        bound: u64,
+    ) -> Result<(), Error> {
+        if bound == 0 {
+            return Err(Error::Synthesis);
+        }
+
+        let num_bytes = bytes_needed(bound - 1);
+        let capacity = 1u128 << (RANGE_CHECK_BITS * num_bytes);
+
+        self.decompose(layouter, value, 0, num_bytes)?;
+        if capacity != bound as u128 {
+            self.decompose(layouter, value, capacity - bound as u128, num_bytes)?;
+        }
+
+        Ok(())
+    }
+
+    fn decompose<F: PrimeFieldBits>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        offset: u128,
+        num_bytes: usize,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                self.selector_init.enable(&mut region, 0)?;
+                value.copy_advice(|| "value", &mut region, self.value, 0)?;
+                region.assign_fixed(|| "offset", self.offset, 0, || Value::known(F::from_u128(offset)))?;
+
+                let mut z = value.value().map(|v| *v + F::from_u128(offset));
+                for row in 0..num_bytes {
+                    self.selector_byte.enable(&mut region, row)?;
+                    region.assign_advice(|| "running_sum", self.running_sum, row, || z)?;
+                    z = z.map(|z| F::from_u128(field_to_u128(&z) >> RANGE_CHECK_BITS));
+                }
+
+                self.selector_end.enable(&mut region, num_bytes)?;
+                region.assign_advice(|| "running_sum", self.running_sum, num_bytes, || z)?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+// Number of bytes needed to represent value (at least one)
+fn bytes_needed(value: u64) -> usize {
+    let bits = 64 - value.leading_zeros() as usize;
+    bits.div_ceil(RANGE_CHECK_BITS).max(1)
+}
+
+// Low 128 bits of a field element (witness generation only)
+pub fn field_to_u128<F: PrimeFieldBits>(value: &F) -> u128 {
+    value
+        .to_le_bits()
+        .iter()
+        .by_vals()
+        .take(128)
+        .enumerate()
+        .fold(0u128, |acc, (i, bit)| acc | ((bit as u128) << i))
+}
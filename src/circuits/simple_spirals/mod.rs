@@ -1,22 +1,64 @@
-use ff::Field;
+use ff::{PrimeField, PrimeFieldBits};
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Table, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+        TableColumn,
+    },
     poly::Rotation,
 };
 
+use crate::circuits::range_check::{field_to_u128, RangeCheckConfig, RANGE_CHECK_BITS};
+use crate::generation::{
+    base_angle_index, calculate_configuration_mapping, generate_background_type, generate_galaxy, micro_triangle_size,
+    particle_progress, spiral_progression, trace_spiral_point, SpiralPointTrace, COS_TABLE,
+    MAX_RADIUS_RATIO, PARTICLES_PER_ARM, SCALE_FACTOR, SIN_TABLE, TRIG_TABLE_SIZE,
+};
+
+// Layout estimate used to size the circuit. The range-check columns are the tallest part:
+// 27 rows per particle plus the seed/configuration checks, and the byte table needs 256 rows.
+const RANGE_CHECK_ROWS_PER_PARTICLE: usize = 27;
+const RANGE_CHECK_ROWS_FIXED: usize = 128;
+const BLINDING_ROWS: usize = 16;
+const MIN_K: u32 = 9;
+
+// Public inputs, in instance column order
+pub const INSTANCE_SEED: usize = 0;
+pub const INSTANCE_VARIANT_ID: usize = 1;
+pub const INSTANCE_SPIRAL_TYPE: usize = 2;
+pub const INSTANCE_NUM_ARMS: usize = 3;
+pub const INSTANCE_BACKGROUND_TYPE: usize = 4;
+pub const INSTANCE_TRIANGLE_COMMITMENT: usize = 5;
+pub const PUBLIC_INPUT_NAMES: [&str; 6] = [
+    "seed", "variant_id", "spiral_type", "num_arms", "background_type", "triangle_commitment",
+];
+
+// Fixed challenge for the running triangle commitment: acc = acc * R + encode(triangle)
+pub const TRIANGLE_COMMITMENT_CHALLENGE: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// Recorded in persisted key manifests. The constraint-system hash catches gate changes;
+// bump this for anything it can't see (lookup table contents, fixed-column layout).
+pub const CIRCUIT_VERSION: u32 = 1;
+
 #[derive(Debug, Clone)]
 pub struct SimpleSpiralsConfig {
-    pub advice: [Column<Advice>; 24], // Keep existing column count
+    pub advice: [Column<Advice>; 51],
+    pub instance: Column<Instance>,
+    pub constants: Column<Fixed>,
+    pub range_check: RangeCheckConfig,
+    pub trig_table: [TableColumn; 6],    // tag, angle_index, |sin|, sin < 0, |cos|, cos < 0
+    pub profile_table: [TableColumn; 6], // tag, spiral_type, particle_index, t, progression, half_size
+    pub arm_table: [TableColumn; 4],     // tag, num_arms, arm_index, base_angle_index
     pub selector_variant: Selector,
     pub selector_spiral: Selector,
     pub selector_config: Selector,
     pub selector_particle: Selector,
     pub selector_triangle: Selector,
+    pub selector_background: Selector,
 }
 
 #[derive(Debug, Clone)]
-pub struct SimpleSpiralsCircuit<F: Field> {
+pub struct SimpleSpiralsCircuit<F: PrimeField> {
     pub seed: Value<F>,
     pub variant_id: Value<F>,
     pub quotient: Value<F>,
@@ -42,7 +84,134 @@ pub struct SimpleSpiralsCircuit<F: Field> {
     pub triangle_metadata: Vec<(Value<F>, Value<F>, Value<F>)>,
 }
 
-impl<F: Field> Circuit<F> for SimpleSpiralsCircuit<F> {
+// Cells produced by the configuration region that later regions copy from
+struct ConfigCells<F: PrimeField> {
+    spiral_type: AssignedCell<F, F>,
+    num_arms: AssignedCell<F, F>,
+    spiral_quotient: AssignedCell<F, F>,
+    arms_quotient: AssignedCell<F, F>,
+    arms_remainder: AssignedCell<F, F>,
+    canvas_size: AssignedCell<F, F>,
+    center: AssignedCell<F, F>,
+    max_radius: AssignedCell<F, F>,
+    max_radius_remainder: AssignedCell<F, F>,
+}
+
+// Cells of one particle row needed by the range checks and the triangle region
+struct ParticleCells<F: PrimeField> {
+    x: AssignedCell<F, F>,
+    y: AssignedCell<F, F>,
+    arm_index: AssignedCell<F, F>,
+    particle_index: AssignedCell<F, F>,
+    radius: AssignedCell<F, F>,
+    radius_remainder: AssignedCell<F, F>,
+    dx: AssignedCell<F, F>,
+    dx_remainder: AssignedCell<F, F>,
+    dy: AssignedCell<F, F>,
+    dy_remainder: AssignedCell<F, F>,
+}
+
+impl<F: PrimeField> SimpleSpiralsCircuit<F> {
+    // Fully-witnessed circuit for a seed, built from the same galaxy the API serves
+    pub fn from_seed(seed: u64, canvas_size: u64) -> Self {
+        let galaxy = generate_galaxy(seed, canvas_size);
+        let known = |value: u64| Value::known(F::from(value));
+
+        Self {
+            seed: known(seed),
+            variant_id: known(galaxy.variant),
+            quotient: known(galaxy.quotient),
+            spiral_type: known(galaxy.spiral_type),
+            num_arms: known(galaxy.num_arms),
+            background_type: known(galaxy.background_type),
+            spiral_quotient: known(galaxy.spiral_quotient),
+            arms_quotient: known(galaxy.arms_quotient),
+            arms_remainder: known(galaxy.arms_remainder),
+            particles_per_arm: known(PARTICLES_PER_ARM),
+            total_particles: known(galaxy.num_arms * PARTICLES_PER_ARM),
+            canvas_size: known(canvas_size),
+            particle_positions: galaxy
+                .particles
+                .iter()
+                .map(|p| (known(p.x), known(p.y)))
+                .collect(),
+            particle_metadata: galaxy
+                .particles
+                .iter()
+                .map(|p| (known(p.arm_index), known(p.particle_index), known(p.angle_index)))
+                .collect(),
+            triangles_per_arm: known(PARTICLES_PER_ARM),
+            total_triangles: known(galaxy.num_arms * PARTICLES_PER_ARM),
+            triangle_vertices: galaxy
+                .triangle_vertices()
+                .into_iter()
+                .map(|(x1, y1, x2, y2, x3, y3)| (known(x1), known(y1), known(x2), known(y2), known(x3), known(y3)))
+                .collect(),
+            triangle_metadata: galaxy
+                .triangle_metadata()
+                .into_iter()
+                .map(|(arm_idx, triangle_idx, triangle_type)| (known(arm_idx), known(triangle_idx), known(triangle_type)))
+                .collect(),
+        }
+    }
+
+    // Witness-free circuit with the layout of any num_arms galaxy (what keygen needs).
+    // Equal to from_seed(seed, _).without_witnesses() for every seed with that arm count.
+    pub fn keygen_shape(num_arms: u64) -> Self {
+        let total = (num_arms * PARTICLES_PER_ARM) as usize;
+
+        Self {
+            seed: Value::unknown(),
+            variant_id: Value::unknown(),
+            quotient: Value::unknown(),
+            spiral_type: Value::unknown(),
+            num_arms: Value::unknown(),
+            background_type: Value::unknown(),
+            spiral_quotient: Value::unknown(),
+            arms_quotient: Value::unknown(),
+            arms_remainder: Value::unknown(),
+            particles_per_arm: Value::unknown(),
+            total_particles: Value::unknown(),
+            canvas_size: Value::unknown(),
+            particle_positions: vec![(Value::unknown(), Value::unknown()); total],
+            particle_metadata: vec![(Value::unknown(), Value::unknown(), Value::unknown()); total],
+            triangles_per_arm: Value::unknown(),
+            total_triangles: Value::unknown(),
+            triangle_vertices: vec![
+                (Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown());
+                total
+            ],
+            triangle_metadata: vec![(Value::unknown(), Value::unknown(), Value::unknown()); total],
+        }
+    }
+
+    // Number of arms implied by the witness vectors, or None for a configuration-only circuit.
+    // Partial galaxies are rejected instead of being silently truncated, and so are particles
+    // without triangles: every arm count draws triangles, and an empty set would commit to 0.
+    pub fn witnessed_arms(&self) -> Result<Option<u64>, Error> {
+        let particles = self.particle_positions.len();
+        let triangles = self.triangle_vertices.len();
+
+        if particles != self.particle_metadata.len() || triangles != self.triangle_metadata.len() {
+            return Err(Error::Synthesis);
+        }
+        if particles == 0 {
+            return if triangles == 0 { Ok(None) } else { Err(Error::Synthesis) };
+        }
+
+        let num_arms = particles as u64 / PARTICLES_PER_ARM;
+        if particles as u64 % PARTICLES_PER_ARM != 0
+            || !(3..=8).contains(&num_arms)
+            || triangles != particles
+        {
+            return Err(Error::Synthesis);
+        }
+
+        Ok(Some(num_arms))
+    }
+}
+
+impl<F: PrimeFieldBits> Circuit<F> for SimpleSpiralsCircuit<F> {
     type Config = SimpleSpiralsConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -60,19 +229,23 @@ impl<F: Field> Circuit<F> for SimpleSpiralsCircuit<F> {
             particles_per_arm: Value::unknown(),
             total_particles: Value::unknown(),
             canvas_size: Value::unknown(),
-            particle_positions: vec![],
-            particle_metadata: vec![],
+            // Keep vector lengths so keygen sees the same region shapes as proving
+            particle_positions: vec![(Value::unknown(), Value::unknown()); self.particle_positions.len()],
+            particle_metadata: vec![(Value::unknown(), Value::unknown(), Value::unknown()); self.particle_metadata.len()],
             triangles_per_arm: Value::unknown(),
             total_triangles: Value::unknown(),
-            triangle_vertices: vec![],
-            triangle_metadata: vec![],
+            triangle_vertices: vec![
+                (Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown());
+                self.triangle_vertices.len()
+            ],
+            triangle_metadata: vec![(Value::unknown(), Value::unknown(), Value::unknown()); self.triangle_metadata.len()],
         }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let advice = [
             meta.advice_column(), // 0: seed
-            meta.advice_column(), // 1: quotient  
+            meta.advice_column(), // 1: quotient
             meta.advice_column(), // 2: variant_id
             meta.advice_column(), // 3: spiral_type
             meta.advice_column(), // 4: num_arms
@@ -96,13 +269,59 @@ impl<F: Field> Circuit<F> for SimpleSpiralsCircuit<F> {
             meta.advice_column(), // 21: triangle_y2
             meta.advice_column(), // 22: triangle_x3
             meta.advice_column(), // 23: triangle_y3
+            meta.advice_column(), // 24: triangle_arm_index
+            meta.advice_column(), // 25: triangle_index
+            meta.advice_column(), // 26: triangle_type
+            meta.advice_column(), // 27: triangle_commitment (running)
+            meta.advice_column(), // 28: background_type
+            // Spiral point trace (see trace_spiral_point)
+            meta.advice_column(), // 29: base_angle_index
+            meta.advice_column(), // 30: t
+            meta.advice_column(), // 31: progression
+            meta.advice_column(), // 32: angle_wrap
+            meta.advice_column(), // 33: center
+            meta.advice_column(), // 34: max_radius
+            meta.advice_column(), // 35: radius
+            meta.advice_column(), // 36: radius_remainder
+            meta.advice_column(), // 37: sin_abs
+            meta.advice_column(), // 38: sin_negative
+            meta.advice_column(), // 39: cos_abs
+            meta.advice_column(), // 40: cos_negative
+            meta.advice_column(), // 41: dx
+            meta.advice_column(), // 42: dx_remainder
+            meta.advice_column(), // 43: dy
+            meta.advice_column(), // 44: dy_remainder
+            meta.advice_column(), // 45: half_size
+            meta.advice_column(), // 46: center_remainder
+            meta.advice_column(), // 47: max_radius_remainder
+            // Background selection
+            meta.advice_column(), // 48: background_quotient (seed / 7)
+            meta.advice_column(), // 49: background_remainder (seed % 7)
+            meta.advice_column(), // 50: background_cycle (seed / 7 / 21)
         ];
-        
+
+        // Public inputs: seed, variant_id, spiral_type, num_arms, background_type, triangle_commitment
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        // Dedicated columns for the byte-wise range checks
+        let range_value = meta.advice_column();
+        let range_running_sum = meta.advice_column();
+        let range_check = RangeCheckConfig::configure(meta, range_value, range_running_sum);
+
+        let trig_table = [(); 6].map(|_| meta.lookup_table_column());
+        let profile_table = [(); 6].map(|_| meta.lookup_table_column());
+        let arm_table = [(); 4].map(|_| meta.lookup_table_column());
+
         let selector_variant = meta.selector();
-        let selector_spiral = meta.selector();
+        let selector_spiral = meta.complex_selector();
         let selector_config = meta.selector();
-        let selector_particle = meta.selector();
-        let selector_triangle = meta.selector();
+        let selector_particle = meta.complex_selector();
+        let selector_triangle = meta.complex_selector();
+        let selector_background = meta.selector();
 
         for col in &advice {
             meta.enable_equality(*col);
@@ -163,7 +382,7 @@ impl<F: Field> Circuit<F> for SimpleSpiralsCircuit<F> {
             vec![
                 // Prove: variant_id = spiral_quotient * 3 + spiral_type
                 s.clone() * (variant_id - (spiral_quotient.clone() * three_expr.clone() + spiral_type)),
-                // Prove: spiral_quotient = arms_quotient * 6 + arms_remainder  
+                // Prove: spiral_quotient = arms_quotient * 6 + arms_remainder
                 s.clone() * (spiral_quotient - (arms_quotient * six_expr + arms_remainder.clone())),
                 // Prove: num_arms = 3 + arms_remainder
                 s.clone() * (num_arms.clone() - (three_expr + arms_remainder)),
@@ -178,58 +397,194 @@ impl<F: Field> Circuit<F> for SimpleSpiralsCircuit<F> {
             ]
         });
 
-        // Constraint 3: Particle position verification
+        // Constraint 3: Canvas geometry (center and max radius, floor division via remainders)
+        meta.create_gate("canvas_geometry", |meta| {
+            let s = meta.query_selector(selector_config);
+            let canvas_size = meta.query_advice(advice[10], Rotation::cur());
+            let center = meta.query_advice(advice[33], Rotation::cur());
+            let max_radius = meta.query_advice(advice[34], Rotation::cur());
+            let center_remainder = meta.query_advice(advice[46], Rotation::cur());
+            let max_radius_remainder = meta.query_advice(advice[47], Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+            let two = Expression::Constant(F::from(2));
+            let ratio = Expression::Constant(F::from(MAX_RADIUS_RATIO));
+            let scale = Expression::Constant(F::from(SCALE_FACTOR as u64));
+
+            vec![
+                // Prove: canvas_size = center * 2 + center_remainder, center_remainder in {0, 1}
+                s.clone() * (canvas_size.clone() - (center * two + center_remainder.clone())),
+                s.clone() * (center_remainder.clone() * (one - center_remainder)),
+                // Prove: canvas_size * 4963 = max_radius * 10000 + max_radius_remainder
+                s * (canvas_size * ratio - (max_radius * scale + max_radius_remainder)),
+            ]
+        });
+
+        // Constraint 4: Particle position verification (mirrors trace_spiral_point)
         meta.create_gate("particle_verification", |meta| {
             let s = meta.query_selector(selector_particle);
             let particle_x = meta.query_advice(advice[11], Rotation::cur());
             let particle_y = meta.query_advice(advice[12], Rotation::cur());
-            let _canvas_size = meta.query_advice(advice[10], Rotation::cur());
+            let angle_index = meta.query_advice(advice[15], Rotation::cur());
+            let base_angle_index = meta.query_advice(advice[29], Rotation::cur());
+            let t = meta.query_advice(advice[30], Rotation::cur());
+            let progression = meta.query_advice(advice[31], Rotation::cur());
+            let angle_wrap = meta.query_advice(advice[32], Rotation::cur());
+            let center = meta.query_advice(advice[33], Rotation::cur());
+            let max_radius = meta.query_advice(advice[34], Rotation::cur());
+            let radius = meta.query_advice(advice[35], Rotation::cur());
+            let radius_remainder = meta.query_advice(advice[36], Rotation::cur());
+            let sin_abs = meta.query_advice(advice[37], Rotation::cur());
+            let sin_negative = meta.query_advice(advice[38], Rotation::cur());
+            let cos_abs = meta.query_advice(advice[39], Rotation::cur());
+            let cos_negative = meta.query_advice(advice[40], Rotation::cur());
+            let dx = meta.query_advice(advice[41], Rotation::cur());
+            let dx_remainder = meta.query_advice(advice[42], Rotation::cur());
+            let dy = meta.query_advice(advice[43], Rotation::cur());
+            let dy_remainder = meta.query_advice(advice[44], Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+            let two = Expression::Constant(F::from(2));
+            let table_size = Expression::Constant(F::from(TRIG_TABLE_SIZE as u64));
+            let thousand = Expression::Constant(F::from(1000));
+            let scale = Expression::Constant(F::from(SCALE_FACTOR as u64));
 
-            // Simple placeholder constraints
             vec![
-                s.clone() * (particle_x.clone() - particle_x),
-                s * (particle_y.clone() - particle_y),
+                // Prove: base_angle_index + progression = angle_wrap * 32 + angle_index, angle_wrap in {0, 1}
+                s.clone() * (base_angle_index + progression - (angle_wrap.clone() * table_size + angle_index)),
+                s.clone() * (angle_wrap.clone() * (one - angle_wrap)),
+                // Prove: t * max_radius = radius * 1000 + radius_remainder
+                s.clone() * (t * max_radius - (radius.clone() * thousand + radius_remainder)),
+                // Prove: radius * |cos| = dx * 10000 + dx_remainder (and likewise dy with |sin|)
+                s.clone() * (radius.clone() * cos_abs - (dx.clone() * scale.clone() + dx_remainder)),
+                s.clone() * (radius * sin_abs - (dy.clone() * scale + dy_remainder)),
+                // Prove: x = center + dx or center - dx depending on the sign of cos (and y with sin)
+                s.clone() * (particle_x - (center.clone() + dx.clone() - two.clone() * cos_negative * dx)),
+                s * (particle_y - (center + dy.clone() - two * sin_negative * dy)),
             ]
         });
 
-        // Constraint 4: OPTIMIZED triangle verification for 23 micro-triangles per arm
+        // Constraint 5: OPTIMIZED triangle verification for 23 micro-triangles per arm
+        // Columns 11/12 hold the particle center copied from the particle region
         meta.create_gate("triangle_verification", |meta| {
             let s = meta.query_selector(selector_triangle);
+            let center_x = meta.query_advice(advice[11], Rotation::cur());
+            let center_y = meta.query_advice(advice[12], Rotation::cur());
             let x1 = meta.query_advice(advice[18], Rotation::cur());
             let y1 = meta.query_advice(advice[19], Rotation::cur());
             let x2 = meta.query_advice(advice[20], Rotation::cur());
             let y2 = meta.query_advice(advice[21], Rotation::cur());
             let x3 = meta.query_advice(advice[22], Rotation::cur());
             let y3 = meta.query_advice(advice[23], Rotation::cur());
+            let triangle_type = meta.query_advice(advice[26], Rotation::cur());
+            let half_size = meta.query_advice(advice[45], Rotation::cur());
 
-            // Basic triangle validation for micro-triangles
             vec![
-                s.clone() * (x1.clone() - x1),
-                s.clone() * (y1.clone() - y1),
-                s.clone() * (x2.clone() - x2),
-                s.clone() * (y2.clone() - y2),
-                s.clone() * (x3.clone() - x3),
-                s * (y3.clone() - y3),
+                // Top point: (cx, cy - half)
+                s.clone() * (x1 - center_x.clone()),
+                s.clone() * (y1 + half_size.clone() - center_y.clone()),
+                // Bottom left: (cx - half, cy + half)
+                s.clone() * (x2 + half_size.clone() - center_x.clone()),
+                s.clone() * (y2 - (center_y.clone() + half_size.clone())),
+                // Bottom right: (cx + half, cy + half)
+                s.clone() * (x3 - (center_x + half_size.clone())),
+                s.clone() * (y3 - (center_y + half_size)),
+                // Type 0 = spiral particle
+                s * triangle_type,
             ]
         });
 
-        // Constraint 5: Trigonometric lookup (unchanged)
-        meta.create_gate("trig_lookup", |meta| {
-            let s = meta.query_selector(selector_spiral);
-            let angle_index = meta.query_advice(advice[15], Rotation::cur());
+        // Constraint 6: Running commitment over the triangle list (exposed as a public input)
+        meta.create_gate("triangle_commitment", |meta| {
+            let s = meta.query_selector(selector_triangle);
+            let parts = [18, 19, 20, 21, 22, 23, 24, 25, 26]
+                .map(|col| meta.query_advice(advice[col], Rotation::cur()));
+            let acc_prev = meta.query_advice(advice[27], Rotation::prev());
+            let acc = meta.query_advice(advice[27], Rotation::cur());
+
+            let encoded = parts
+                .into_iter()
+                .zip(triangle_encoding_coefficients::<F>())
+                .fold(Expression::Constant(F::ZERO), |sum, (part, coeff)| {
+                    sum + part * Expression::Constant(coeff)
+                });
+            let challenge = Expression::Constant(F::from(TRIANGLE_COMMITMENT_CHALLENGE));
 
             vec![
-                s * (angle_index.clone() - angle_index),
+                s * (acc - (acc_prev * challenge + encoded)),
             ]
         });
 
-        SimpleSpiralsConfig { 
-            advice, 
-            selector_variant, 
+        // Constraint 7: Background type (matches generate_background_type)
+        meta.create_gate("background_selection", |meta| {
+            let s = meta.query_selector(selector_background);
+            let seed = meta.query_advice(advice[0], Rotation::cur());
+            let background_type = meta.query_advice(advice[28], Rotation::cur());
+            let background_quotient = meta.query_advice(advice[48], Rotation::cur());
+            let background_remainder = meta.query_advice(advice[49], Rotation::cur());
+            let background_cycle = meta.query_advice(advice[50], Rotation::cur());
+
+            let seven = Expression::Constant(F::from(7));
+            let twenty_one = Expression::Constant(F::from(21));
+
+            vec![
+                // Prove: seed = background_quotient * 7 + background_remainder
+                s.clone() * (seed - (background_quotient.clone() * seven + background_remainder)),
+                // Prove: background_quotient = background_cycle * 21 + background_type
+                s * (background_quotient - (background_cycle * twenty_one + background_type)),
+            ]
+        });
+
+        // Lookup 1: (angle_index, |sin|, sin < 0, |cos|, cos < 0) is a row of the trig table
+        meta.lookup("trig_lookup", |meta| {
+            let s = meta.query_selector(selector_spiral);
+            let inputs = [15, 37, 38, 39, 40]
+                .map(|col| meta.query_advice(advice[col], Rotation::cur()));
+
+            tagged_lookup(s, inputs, &trig_table)
+        });
+
+        // Lookup 2: (spiral_type, particle_index, t, progression) is a row of the profile table
+        meta.lookup("particle_profile", |meta| {
+            let s = meta.query_selector(selector_particle);
+            let inputs = [3, 14, 30, 31]
+                .map(|col| meta.query_advice(advice[col], Rotation::cur()));
+
+            tagged_lookup(s, inputs, &profile_table[..5])
+        });
+
+        // Lookup 3: (num_arms, arm_index, base_angle_index) is a row of the arm table
+        meta.lookup("arm_angle", |meta| {
+            let s = meta.query_selector(selector_particle);
+            let inputs = [4, 13, 29]
+                .map(|col| meta.query_advice(advice[col], Rotation::cur()));
+
+            tagged_lookup(s, inputs, &arm_table)
+        });
+
+        // Lookup 4: triangle half size matches the particle index
+        meta.lookup("triangle_size", |meta| {
+            let s = meta.query_selector(selector_triangle);
+            let inputs = [25, 45]
+                .map(|col| meta.query_advice(advice[col], Rotation::cur()));
+
+            tagged_lookup(s, inputs, &[profile_table[0], profile_table[2], profile_table[5]])
+        });
+
+        SimpleSpiralsConfig {
+            advice,
+            instance,
+            constants,
+            range_check,
+            trig_table,
+            profile_table,
+            arm_table,
+            selector_variant,
             selector_spiral,
             selector_config,
             selector_particle,
             selector_triangle,
+            selector_background,
         }
     }
 
@@ -238,235 +593,440 @@ impl<F: Field> Circuit<F> for SimpleSpiralsCircuit<F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
+        let witnessed_arms = self.witnessed_arms()?;
+        config.load_tables(&mut layouter)?;
+
         // Region 1: Variant selection (unchanged)
-        layouter.assign_region(
+        let (seed_cell, quotient_cell, variant_cell) = layouter.assign_region(
             || "variant selection",
             |mut region| {
                 config.selector_variant.enable(&mut region, 0)?;
 
-                region.assign_advice(|| "seed", config.advice[0], 0, || self.seed)?;
-                region.assign_advice(|| "quotient", config.advice[1], 0, || self.quotient)?;
-                region.assign_advice(|| "variant_id", config.advice[2], 0, || self.variant_id)?;
+                let seed = region.assign_advice(|| "seed", config.advice[0], 0, || self.seed)?;
+                let quotient = region.assign_advice(|| "quotient", config.advice[1], 0, || self.quotient)?;
+                let variant_id = region.assign_advice(|| "variant_id", config.advice[2], 0, || self.variant_id)?;
 
-                Ok(())
+                Ok((seed, quotient, variant_id))
             },
         )?;
 
+        // seed is a u64 and variant_id < 41, so seed = quotient * 41 + variant_id has a unique solution
+        config.range_check.range_check(&mut layouter, &seed_cell, 8)?;
+        config.range_check.range_check(&mut layouter, &quotient_cell, 8)?;
+        config.range_check.less_than(&mut layouter, &variant_cell, 41)?;
+
+        // Region 1b: Background selection from the same seed
+        let (background_cells, background_cell) = layouter.assign_region(
+            || "background selection",
+            |mut region| {
+                config.selector_background.enable(&mut region, 0)?;
+
+                let seed = self.seed.map(|s| field_to_u128(&s) as u64);
+                seed_cell.copy_advice(|| "seed", &mut region, config.advice[0], 0)?;
+                let background_quotient = region.assign_advice(
+                    || "background_quotient",
+                    config.advice[48],
+                    0,
+                    || seed.map(|s| F::from(s / 7)),
+                )?;
+                let background_remainder = region.assign_advice(
+                    || "background_remainder",
+                    config.advice[49],
+                    0,
+                    || seed.map(|s| F::from(s % 7)),
+                )?;
+                let background_cycle = region.assign_advice(
+                    || "background_cycle",
+                    config.advice[50],
+                    0,
+                    || seed.map(|s| F::from(s / 7 / 21)),
+                )?;
+                let background_type = region.assign_advice(|| "background_type", config.advice[28], 0, || self.background_type)?;
+
+                Ok(([background_quotient, background_remainder, background_cycle], background_type))
+            },
+        )?;
+
+        // Remainders below 7 and 21, quotients within a u64, so the decomposition is unique
+        let [background_quotient, background_remainder, background_cycle] = &background_cells;
+        config.range_check.range_check(&mut layouter, background_quotient, 8)?;
+        config.range_check.less_than(&mut layouter, background_remainder, 7)?;
+        config.range_check.range_check(&mut layouter, background_cycle, 8)?;
+        config.range_check.less_than(&mut layouter, &background_cell, 21)?;
+
         // Region 2: OPTIMIZED configuration mapping for 23 triangles
-        layouter.assign_region(
+        let config_cells = layouter.assign_region(
             || "configuration mapping",
             |mut region| {
                 config.selector_config.enable(&mut region, 0)?;
 
-                region.assign_advice(|| "variant_id", config.advice[2], 0, || self.variant_id)?;
-                region.assign_advice(|| "spiral_type", config.advice[3], 0, || self.spiral_type)?;
-                region.assign_advice(|| "num_arms", config.advice[4], 0, || self.num_arms)?;
-                region.assign_advice(|| "spiral_quotient", config.advice[5], 0, || self.spiral_quotient)?;
-                region.assign_advice(|| "arms_quotient", config.advice[6], 0, || self.arms_quotient)?;
-                region.assign_advice(|| "arms_remainder", config.advice[7], 0, || self.arms_remainder)?;
+                // Same variant_id as the variant selection region
+                variant_cell.copy_advice(|| "variant_id", &mut region, config.advice[2], 0)?;
+                let spiral_type = region.assign_advice(|| "spiral_type", config.advice[3], 0, || self.spiral_type)?;
+                let num_arms = region.assign_advice(|| "num_arms", config.advice[4], 0, || self.num_arms)?;
+                if let Some(arms) = witnessed_arms {
+                    // The particle list must cover every arm of this galaxy
+                    self.num_arms.error_if_known_and(|n| *n != F::from(arms))?;
+                    region.constrain_constant(num_arms.cell(), F::from(arms))?;
+                }
+                let spiral_quotient = region.assign_advice(|| "spiral_quotient", config.advice[5], 0, || self.spiral_quotient)?;
+                let arms_quotient = region.assign_advice(|| "arms_quotient", config.advice[6], 0, || self.arms_quotient)?;
+                let arms_remainder = region.assign_advice(|| "arms_remainder", config.advice[7], 0, || self.arms_remainder)?;
                 region.assign_advice(|| "particles_per_arm", config.advice[8], 0, || self.particles_per_arm)?;
                 region.assign_advice(|| "total_particles", config.advice[9], 0, || self.total_particles)?;
-                region.assign_advice(|| "canvas_size", config.advice[10], 0, || self.canvas_size)?;
+                let canvas_size = region.assign_advice(|| "canvas_size", config.advice[10], 0, || self.canvas_size)?;
                 region.assign_advice(|| "triangles_per_arm", config.advice[16], 0, || self.triangles_per_arm)?;
                 region.assign_advice(|| "total_triangles", config.advice[17], 0, || self.total_triangles)?;
 
-                Ok(())
+                // Canvas geometry shared by every particle
+                let canvas = self.canvas_size.map(|c| field_to_u128(&c) as u64);
+                let center = region.assign_advice(|| "center", config.advice[33], 0, || canvas.map(|c| F::from(c / 2)))?;
+                region.assign_advice(|| "center_remainder", config.advice[46], 0, || canvas.map(|c| F::from(c % 2)))?;
+                let max_radius = region.assign_advice(
+                    || "max_radius",
+                    config.advice[34],
+                    0,
+                    || canvas.map(|c| F::from((c * MAX_RADIUS_RATIO) / SCALE_FACTOR as u64)),
+                )?;
+                let max_radius_remainder = region.assign_advice(
+                    || "max_radius_remainder",
+                    config.advice[47],
+                    0,
+                    || canvas.map(|c| F::from((c * MAX_RADIUS_RATIO) % SCALE_FACTOR as u64)),
+                )?;
+
+                Ok(ConfigCells {
+                    spiral_type,
+                    num_arms,
+                    spiral_quotient,
+                    arms_quotient,
+                    arms_remainder,
+                    canvas_size,
+                    center,
+                    max_radius,
+                    max_radius_remainder,
+                })
             },
         )?;
 
+        // Remainders of the mod-3 / mod-6 decompositions, quotients kept small so nothing wraps
+        config.range_check.less_than(&mut layouter, &config_cells.spiral_type, 3)?;
+        config.range_check.range_check(&mut layouter, &config_cells.spiral_quotient, 1)?;
+        config.range_check.less_than(&mut layouter, &config_cells.arms_remainder, 6)?;
+        config.range_check.range_check(&mut layouter, &config_cells.arms_quotient, 1)?;
+
+        // Canvas coordinates fit in 16 bits, so the divisions above cannot wrap
+        config.range_check.range_check(&mut layouter, &config_cells.canvas_size, 2)?;
+        config.range_check.range_check(&mut layouter, &config_cells.center, 2)?;
+        config.range_check.range_check(&mut layouter, &config_cells.max_radius, 2)?;
+        config.range_check.less_than(&mut layouter, &config_cells.max_radius_remainder, SCALE_FACTOR as u64)?;
+
         // Region 3: OPTIMIZED particle generation (23 particles per arm)
-        layouter.assign_region(
+        let particle_cells = layouter.assign_region(
             || "particle generation",
             |mut region| {
-                for (i, ((x, y), (arm_idx, particle_idx, angle_idx))) in 
-                    self.particle_positions.iter().zip(self.particle_metadata.iter()).enumerate() 
+                let mut cells = Vec::new();
+
+                for (i, ((x, y), (arm_idx, particle_idx, angle_idx))) in
+                    self.particle_positions.iter().zip(self.particle_metadata.iter()).enumerate()
                 {
-                    if i < 30 { // Reduced circuit size for testing (was 50)
-                        config.selector_particle.enable(&mut region, i)?;
-                        
-                        region.assign_advice(|| "particle_x", config.advice[11], i, || *x)?;
-                        region.assign_advice(|| "particle_y", config.advice[12], i, || *y)?;
-                        region.assign_advice(|| "arm_index", config.advice[13], i, || *arm_idx)?;
-                        region.assign_advice(|| "particle_index", config.advice[14], i, || *particle_idx)?;
-                        region.assign_advice(|| "angle_index", config.advice[15], i, || *angle_idx)?;
-                        region.assign_advice(|| "canvas_size", config.advice[10], i, || self.canvas_size)?;
-                    }
+                    config.selector_particle.enable(&mut region, i)?;
+                    config.selector_spiral.enable(&mut region, i)?;
+
+                    let trace = arm_idx
+                        .zip(*particle_idx)
+                        .zip(self.num_arms)
+                        .zip(self.spiral_type)
+                        .zip(self.canvas_size)
+                        .map(|((((arm, particle), arms), spiral), canvas)| {
+                            trace_spiral_point(
+                                field_to_u128(&arm) as u64,
+                                field_to_u128(&particle) as u64,
+                                field_to_u128(&arms) as u64,
+                                field_to_u128(&spiral) as u64,
+                                field_to_u128(&canvas) as u64,
+                            )
+                        });
+                    let witness = |value: fn(&SpiralPointTrace) -> u64| trace.map(|t| F::from(value(&t)));
+
+                    let x = region.assign_advice(|| "particle_x", config.advice[11], i, || *x)?;
+                    let y = region.assign_advice(|| "particle_y", config.advice[12], i, || *y)?;
+                    // Particle i is always (arm i / 23, index i % 23), fixed by the layout
+                    let expected_arm = F::from(i as u64 / PARTICLES_PER_ARM);
+                    let expected_index = F::from(i as u64 % PARTICLES_PER_ARM);
+                    arm_idx.error_if_known_and(|a| *a != expected_arm)?;
+                    particle_idx.error_if_known_and(|p| *p != expected_index)?;
+                    let arm_index = region.assign_advice_from_constant(|| "arm_index", config.advice[13], i, expected_arm)?;
+                    let particle_index = region.assign_advice_from_constant(|| "particle_index", config.advice[14], i, expected_index)?;
+                    region.assign_advice(|| "angle_index", config.advice[15], i, || *angle_idx)?;
+
+                    // Configuration shared with region 2
+                    config_cells.spiral_type.copy_advice(|| "spiral_type", &mut region, config.advice[3], i)?;
+                    config_cells.num_arms.copy_advice(|| "num_arms", &mut region, config.advice[4], i)?;
+                    config_cells.center.copy_advice(|| "center", &mut region, config.advice[33], i)?;
+                    config_cells.max_radius.copy_advice(|| "max_radius", &mut region, config.advice[34], i)?;
+
+                    region.assign_advice(|| "base_angle_index", config.advice[29], i, || witness(|t| t.base_angle_index))?;
+                    region.assign_advice(|| "t", config.advice[30], i, || witness(|t| t.t))?;
+                    region.assign_advice(|| "progression", config.advice[31], i, || witness(|t| t.progression))?;
+                    region.assign_advice(|| "angle_wrap", config.advice[32], i, || witness(|t| t.angle_wrap))?;
+                    let radius = region.assign_advice(|| "radius", config.advice[35], i, || witness(|t| t.radius))?;
+                    let radius_remainder = region.assign_advice(|| "radius_remainder", config.advice[36], i, || witness(|t| t.radius_remainder))?;
+                    region.assign_advice(|| "sin_abs", config.advice[37], i, || witness(|t| t.sin_abs))?;
+                    region.assign_advice(|| "sin_negative", config.advice[38], i, || witness(|t| t.sin_negative))?;
+                    region.assign_advice(|| "cos_abs", config.advice[39], i, || witness(|t| t.cos_abs))?;
+                    region.assign_advice(|| "cos_negative", config.advice[40], i, || witness(|t| t.cos_negative))?;
+                    let dx = region.assign_advice(|| "dx", config.advice[41], i, || witness(|t| t.dx))?;
+                    let dx_remainder = region.assign_advice(|| "dx_remainder", config.advice[42], i, || witness(|t| t.dx_remainder))?;
+                    let dy = region.assign_advice(|| "dy", config.advice[43], i, || witness(|t| t.dy))?;
+                    let dy_remainder = region.assign_advice(|| "dy_remainder", config.advice[44], i, || witness(|t| t.dy_remainder))?;
+
+                    cells.push(ParticleCells {
+                        x,
+                        y,
+                        arm_index,
+                        particle_index,
+                        radius,
+                        radius_remainder,
+                        dx,
+                        dx_remainder,
+                        dy,
+                        dy_remainder,
+                    });
                 }
 
-                Ok(())
+                Ok(cells)
             },
         )?;
 
+        // Remainders must be canonical for the floor divisions in "particle_verification"
+        for particle in &particle_cells {
+            config.range_check.range_check(&mut layouter, &particle.radius, 2)?;
+            config.range_check.less_than(&mut layouter, &particle.radius_remainder, 1000)?;
+            config.range_check.range_check(&mut layouter, &particle.dx, 2)?;
+            config.range_check.less_than(&mut layouter, &particle.dx_remainder, SCALE_FACTOR as u64)?;
+            config.range_check.range_check(&mut layouter, &particle.dy, 2)?;
+            config.range_check.less_than(&mut layouter, &particle.dy_remainder, SCALE_FACTOR as u64)?;
+        }
+
         // Region 4: OPTIMIZED triangle generation (23 micro-triangles per arm)
-        layouter.assign_region(
+        // Row 0 seeds the running commitment with zero, triangle i lives on row i + 1
+        let commitment_cell = layouter.assign_region(
             || "triangle generation",
             |mut region| {
-                for (i, ((x1, y1, x2, y2, x3, y3), (_arm_idx, _triangle_idx, _triangle_type))) in 
-                    self.triangle_vertices.iter().zip(self.triangle_metadata.iter()).enumerate() 
+                let coefficients = triangle_encoding_coefficients::<F>();
+                let challenge = Value::known(F::from(TRIANGLE_COMMITMENT_CHALLENGE));
+
+                let mut commitment = region.assign_advice_from_constant(
+                    || "triangle_commitment_init",
+                    config.advice[27],
+                    0,
+                    F::ZERO,
+                )?;
+
+                for (i, ((x1, y1, x2, y2, x3, y3), (arm_idx, triangle_idx, triangle_type))) in
+                    self.triangle_vertices.iter().zip(self.triangle_metadata.iter()).enumerate()
                 {
-                    if i < 30 { // Reduced circuit size for testing
-                        config.selector_triangle.enable(&mut region, i)?;
-                        
-                        region.assign_advice(|| "triangle_x1", config.advice[18], i, || *x1)?;
-                        region.assign_advice(|| "triangle_y1", config.advice[19], i, || *y1)?;
-                        region.assign_advice(|| "triangle_x2", config.advice[20], i, || *x2)?;
-                        region.assign_advice(|| "triangle_y2", config.advice[21], i, || *y2)?;
-                        region.assign_advice(|| "triangle_x3", config.advice[22], i, || *x3)?;
-                        region.assign_advice(|| "triangle_y3", config.advice[23], i, || *y3)?;
-                    }
+                    // Triangle i is drawn around particle i
+                    let particle = particle_cells.get(i).ok_or(Error::Synthesis)?;
+                    let row = i + 1;
+                    config.selector_triangle.enable(&mut region, row)?;
+
+                    particle.x.copy_advice(|| "center_x", &mut region, config.advice[11], row)?;
+                    particle.y.copy_advice(|| "center_y", &mut region, config.advice[12], row)?;
+                    let half_size = particle
+                        .particle_index
+                        .value()
+                        .map(|p| F::from(micro_triangle_size(field_to_u128(p) as u64) / 2));
+                    region.assign_advice(|| "half_size", config.advice[45], row, || half_size)?;
+
+                    region.assign_advice(|| "triangle_x1", config.advice[18], row, || *x1)?;
+                    region.assign_advice(|| "triangle_y1", config.advice[19], row, || *y1)?;
+                    region.assign_advice(|| "triangle_x2", config.advice[20], row, || *x2)?;
+                    region.assign_advice(|| "triangle_y2", config.advice[21], row, || *y2)?;
+                    region.assign_advice(|| "triangle_x3", config.advice[22], row, || *x3)?;
+                    region.assign_advice(|| "triangle_y3", config.advice[23], row, || *y3)?;
+                    let arm_cell = region.assign_advice(|| "triangle_arm_index", config.advice[24], row, || *arm_idx)?;
+                    let index_cell = region.assign_advice(|| "triangle_index", config.advice[25], row, || *triangle_idx)?;
+                    region.assign_advice(|| "triangle_type", config.advice[26], row, || *triangle_type)?;
+
+                    region.constrain_equal(arm_cell.cell(), particle.arm_index.cell())?;
+                    region.constrain_equal(index_cell.cell(), particle.particle_index.cell())?;
+
+                    let encoded = [*x1, *y1, *x2, *y2, *x3, *y3, *arm_idx, *triangle_idx, *triangle_type]
+                        .into_iter()
+                        .zip(coefficients)
+                        .fold(Value::known(F::ZERO), |sum, (part, coeff)| sum + part * Value::known(coeff));
+                    let next = commitment.value().copied() * challenge + encoded;
+
+                    commitment = region.assign_advice(|| "triangle_commitment", config.advice[27], row, || next)?;
                 }
 
-                Ok(())
+                Ok(commitment)
             },
         )?;
 
+        // Expose seed and derived configuration as public inputs
+        layouter.constrain_instance(seed_cell.cell(), config.instance, INSTANCE_SEED)?;
+        layouter.constrain_instance(variant_cell.cell(), config.instance, INSTANCE_VARIANT_ID)?;
+        layouter.constrain_instance(config_cells.spiral_type.cell(), config.instance, INSTANCE_SPIRAL_TYPE)?;
+        layouter.constrain_instance(config_cells.num_arms.cell(), config.instance, INSTANCE_NUM_ARMS)?;
+        layouter.constrain_instance(background_cell.cell(), config.instance, INSTANCE_BACKGROUND_TYPE)?;
+        layouter.constrain_instance(commitment_cell.cell(), config.instance, INSTANCE_TRIANGLE_COMMITMENT)?;
+
         Ok(())
     }
 }
 
-// Helper function to calculate configuration mapping (unchanged)
-pub fn calculate_configuration_mapping(variant_id: u64) -> (u64, u64, u64, u64, u64) {
-    let spiral_type = variant_id % 3;
-    let spiral_quotient = variant_id / 3;
-    let arms_remainder = spiral_quotient % 6;
-    let arms_quotient = spiral_quotient / 6;
-    let num_arms = 3 + arms_remainder;
-    
-    (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder)
-}
+impl SimpleSpiralsConfig {
+    // Load the fixed lookup tables. Row 0 of each tagged table is all zeros (tag 0)
+    // so that rows with the selector disabled always find a match.
+    fn load_tables<F: PrimeFieldBits>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.range_check.load_table(layouter)?;
+
+        layouter.assign_table(
+            || "trig table",
+            |mut table| {
+                let mut rows = vec![[0u64; 6]];
+                for (angle, (sin_val, cos_val)) in SIN_TABLE.iter().zip(COS_TABLE.iter()).enumerate() {
+                    rows.push([
+                        1,
+                        angle as u64,
+                        sin_val.unsigned_abs(),
+                        (*sin_val < 0) as u64,
+                        cos_val.unsigned_abs(),
+                        (*cos_val < 0) as u64,
+                    ]);
+                }
+                assign_table_rows(&mut table, &self.trig_table, &rows)
+            },
+        )?;
+
+        layouter.assign_table(
+            || "particle profile table",
+            |mut table| {
+                let mut rows = vec![[0u64; 6]];
+                for spiral_type in 0..3 {
+                    for particle_index in 0..PARTICLES_PER_ARM {
+                        let t = particle_progress(particle_index);
+                        rows.push([
+                            1,
+                            spiral_type,
+                            particle_index,
+                            t,
+                            spiral_progression(spiral_type, t),
+                            micro_triangle_size(particle_index) / 2,
+                        ]);
+                    }
+                }
+                assign_table_rows(&mut table, &self.profile_table, &rows)
+            },
+        )?;
 
-// Helper function to generate background type from seed (UPDATED to 21 types)
-pub fn generate_background_type(seed: u64) -> u64 {
-    (seed / 7) % 21 // 0-20 for 21 background types
+        layouter.assign_table(
+            || "arm angle table",
+            |mut table| {
+                let mut rows = vec![[0u64; 4]];
+                for num_arms in 3..=8u64 {
+                    for arm_index in 0..num_arms {
+                        rows.push([1, num_arms, arm_index, base_angle_index(arm_index, num_arms)]);
+                    }
+                }
+                assign_table_rows(&mut table, &self.arm_table, &rows)
+            },
+        )
+    }
 }
 
-// OPTIMIZED: Generate spiral particles (23 per arm instead of 69)
-pub fn generate_spiral_particles(
-    spiral_type: u64,
-    num_arms: u64,
-    canvas_size: u64,
-) -> (Vec<(u64, u64)>, Vec<(u64, u64, u64)>) {
-    let particles_per_arm = 23u64; // OPTIMIZED: Reduced from 69
-    let mut positions = Vec::new();
-    let mut metadata = Vec::new();
-
-    for arm_index in 0..num_arms {
-        for particle_index in 0..particles_per_arm {
-            let (x, y, angle_index) = calculate_spiral_point(
-                arm_index,
-                particle_index,
-                num_arms,
-                spiral_type,
-                canvas_size,
-            );
-            
-            positions.push((x, y));
-            metadata.push((arm_index, particle_index, angle_index));
+fn assign_table_rows<F: PrimeField, const N: usize>(
+    table: &mut Table<'_, F>,
+    columns: &[TableColumn; N],
+    rows: &[[u64; N]],
+) -> Result<(), Error> {
+    for (offset, row) in rows.iter().enumerate() {
+        for (column, value) in columns.iter().zip(row.iter()) {
+            table.assign_cell(|| "table cell", *column, offset, || Value::known(F::from(*value)))?;
         }
     }
+    Ok(())
+}
+
+// Lookup inputs gated by a selector; the selector itself is matched against the tag column
+fn tagged_lookup<F: PrimeField, const N: usize>(
+    selector: Expression<F>,
+    inputs: [Expression<F>; N],
+    table: &[TableColumn],
+) -> Vec<(Expression<F>, TableColumn)> {
+    assert_eq!(table.len(), N + 1, "lookup table must have a tag column plus one per input");
 
-    (positions, metadata)
+    let mut lookups = vec![(selector.clone(), table[0])];
+    lookups.extend(
+        inputs
+            .into_iter()
+            .zip(table[1..].iter())
+            .map(|(input, column)| (selector.clone() * input, *column)),
+    );
+    lookups
 }
 
-// OPTIMIZED: Generate micro-triangles for optimized particle effect
-pub fn generate_spiral_triangles(
-    spiral_type: u64,
-    num_arms: u64,
-    canvas_size: u64,
-) -> (Vec<(u64, u64, u64, u64, u64, u64)>, Vec<(u64, u64, u64)>) {
-    let (positions, _metadata) = generate_spiral_particles(spiral_type, num_arms, canvas_size);
-    let particles_per_arm = 23u64; // OPTIMIZED: Reduced from 69
-    
-    let mut triangle_vertices = Vec::new();
-    let mut triangle_metadata = Vec::new();
-
-    for arm_index in 0..num_arms {
-        for particle_index in 0..particles_per_arm {
-            let particle_idx = (arm_index * particles_per_arm + particle_index) as usize;
-            
-            if particle_idx < positions.len() {
-                let (center_x, center_y) = positions[particle_idx];
-                
-                // CREATE MICRO-TRIANGLES: Optimized size for gas efficiency
-                let base_size = 6u64;
-                let size_reduction = (particle_index * 3) / particles_per_arm; // Faster reduction for 23 particles
-                let triangle_size = (base_size - size_reduction).max(2); // Minimum 2 pixels
-                
-                let (x1, y1, x2, y2, x3, y3) = create_micro_triangle(center_x, center_y, triangle_size);
-                
-                triangle_vertices.push((x1, y1, x2, y2, x3, y3));
-                triangle_metadata.push((arm_index, particle_index, 0)); // Type 0 = spiral particle
-            }
-        }
+// Powers of 2^16 used to pack x1, y1, x2, y2, x3, y3, arm, index, type into one field element
+fn triangle_encoding_coefficients<F: PrimeField>() -> [F; 9] {
+    let shift = F::from(1u64 << 16);
+    let mut coefficients = [F::ONE; 9];
+    for i in 1..coefficients.len() {
+        coefficients[i] = coefficients[i - 1] * shift;
     }
+    coefficients
+}
+
+// Off-circuit value of the triangle commitment (matches the "triangle_commitment" gate)
+pub fn calculate_triangle_commitment<F: PrimeField>(
+    triangle_vertices: &[(u64, u64, u64, u64, u64, u64)],
+    triangle_metadata: &[(u64, u64, u64)],
+) -> F {
+    let coefficients = triangle_encoding_coefficients::<F>();
+    let challenge = F::from(TRIANGLE_COMMITMENT_CHALLENGE);
 
-    (triangle_vertices, triangle_metadata)
+    triangle_vertices
+        .iter()
+        .zip(triangle_metadata.iter())
+        .fold(F::ZERO, |acc, ((x1, y1, x2, y2, x3, y3), (arm_idx, triangle_idx, triangle_type))| {
+            let encoded = [*x1, *y1, *x2, *y2, *x3, *y3, *arm_idx, *triangle_idx, *triangle_type]
+                .into_iter()
+                .zip(coefficients)
+                .fold(F::ZERO, |sum, (part, coeff)| sum + F::from(part) * coeff);
+            acc * challenge + encoded
+        })
 }
 
-// Create micro-triangles for optimized particle effect
-fn create_micro_triangle(center_x: u64, center_y: u64, size: u64) -> (u64, u64, u64, u64, u64, u64) {
-    let half_size = size / 2;
-    
-    // Create small triangular "star" particle
-    let x1 = center_x;                    // Top point
-    let y1 = center_y.saturating_sub(half_size);
-    let x2 = center_x.saturating_sub(half_size); // Bottom left
-    let y2 = center_y + half_size;
-    let x3 = center_x + half_size;        // Bottom right  
-    let y3 = center_y + half_size;
-    
-    (x1, y1, x2, y2, x3, y3)
+// Public inputs for a seed, in instance column order
+pub fn calculate_public_inputs<F: PrimeField>(
+    seed: u64,
+    triangle_vertices: &[(u64, u64, u64, u64, u64, u64)],
+    triangle_metadata: &[(u64, u64, u64)],
+) -> Vec<F> {
+    let variant_id = seed % 41;
+    let (spiral_type, num_arms, _, _, _) = calculate_configuration_mapping(variant_id);
+
+    vec![
+        F::from(seed),
+        F::from(variant_id),
+        F::from(spiral_type),
+        F::from(num_arms),
+        F::from(generate_background_type(seed)),
+        calculate_triangle_commitment(triangle_vertices, triangle_metadata),
+    ]
 }
 
-// FIXED: spiral point calculation with proper trigonometry
-pub fn calculate_spiral_point(
-    arm_index: u64,
-    particle_index: u64, 
-    total_arms: u64,
-    spiral_type: u64,
-    canvas_size: u64,
-) -> (u64, u64, u64) {
-    const TRIG_TABLE_SIZE: usize = 32;
-    const SCALE_FACTOR: i64 = 10000;
-    
-    const SIN_TABLE: [i64; TRIG_TABLE_SIZE] = [
-        0, 1951, 3827, 5556, 7071, 8315, 9239, 9808,
-        10000, 9808, 9239, 8315, 7071, 5556, 3827, 1951,
-        0, -1951, -3827, -5556, -7071, -8315, -9239, -9808,
-        -10000, -9808, -9239, -8315, -7071, -5556, -3827, -1951,
-    ];
-    
-    const COS_TABLE: [i64; TRIG_TABLE_SIZE] = [
-        10000, 9808, 9239, 8315, 7071, 5556, 3827, 1951,
-        0, -1951, -3827, -5556, -7071, -8315, -9239, -9808,
-        -10000, -9808, -9239, -8315, -7071, -5556, -3827, -1951,
-        0, 1951, 3827, 5556, 7071, 8315, 9239, 9808,
-    ];
-    
-    let particles_per_arm = 23u64; // OPTIMIZED: Reduced particle count
-    let max_radius = (canvas_size * 4963) / 10000; // Keep same radius ratio
-    
-    let base_angle_index = (arm_index * TRIG_TABLE_SIZE as u64) / total_arms;
-    let t = (particle_index * 1000) / particles_per_arm;
-    
-    // OPTIMIZED SPIRAL TYPES: Enhanced distribution for 23 particles
-    let progression = match spiral_type {
-        0 => (t * 18) / 1000, // Tight spiral - adjusted for 23 particles
-        1 => (t * 5) / 1000,  // Loose spiral - adjusted for 23 particles
-        _ => (t * 10) / 1000, // Classic spiral - adjusted for 23 particles
-    };
-    
-    let angle_index = (base_angle_index + progression) % (TRIG_TABLE_SIZE as u64);
-    let radius = (t * max_radius) / 1000;
-    
-    let sin_val = SIN_TABLE[angle_index as usize];
-    let cos_val = COS_TABLE[angle_index as usize];
-    
-    let center = canvas_size / 2;
-    
-    // FIXED: Remove .abs() and handle negative coordinates properly
-    let x = ((center as i64) + ((radius as i64 * cos_val) / SCALE_FACTOR)).max(0) as u64;
-    let y = ((center as i64) + ((radius as i64 * sin_val) / SCALE_FACTOR)).max(0) as u64;
-    
-    (x, y, angle_index)
-}
\ No newline at end of file
+// Smallest k whose 2^k rows fit a galaxy with num_arms arms (0 for a configuration-only circuit)
+pub fn circuit_k(num_arms: u64) -> u32 {
+    let particles = (num_arms * PARTICLES_PER_ARM) as usize;
+    let rows = (RANGE_CHECK_ROWS_FIXED + RANGE_CHECK_ROWS_PER_PARTICLE * particles).max(1 << RANGE_CHECK_BITS)
+        + BLINDING_ROWS;
+
+    let mut k = MIN_K;
+    while (1usize << k) < rows {
+        k += 1;
+    }
+    k
+}
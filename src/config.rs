@@ -0,0 +1,307 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+
+use crate::api::DEFAULT_CANVAS_SIZE;
+use crate::evm_sim::DEFAULT_ARTIFACT_PATH;
+use crate::prices::PriceSourceOptions;
+
+// Used when neither --config nor ZKBG_CONFIG is given and the file exists
+pub const DEFAULT_CONFIG_FILE: &str = "zkbg.toml";
+
+// Below this the spiral saturates at the canvas edge and can't be proven
+pub const MIN_CANVAS_SIZE: u64 = 128;
+// Canvas geometry is range-checked to 2 bytes in the circuit
+pub const MAX_CANVAS_SIZE: u64 = 65535;
+
+const PRICE_SOURCES: [&str; 6] = ["etherscan", "coingecko", "static", "json-rpc", "rpc", "mock"];
+
+pub const USAGE: &str = "\
+Usage: zkbg [OPTIONS]
+
+Settings are layered: defaults < TOML file < environment < flags.
+
+  --config <FILE>               TOML config file          [env: ZKBG_CONFIG, default: zkbg.toml if present]
+  --bind <ADDR>                 Listen address            [env: ZKBG_BIND, default: 127.0.0.1:3030]
+  --static-dir <DIR>            Visualizer files          [env: ZKBG_STATIC_DIR, default: static]
+  --keys-dir <DIR>              Persisted proving keys    [env: ZKBG_KEYS_DIR, default: keys]
+  --artifact-path <FILE>        Compiled zkBgNFT for gas simulation [env: ZKBG_ARTIFACT_PATH, default: blockchain/artifacts/contracts/zkBgNFT.sol/zkBgNFT.json]
+  --default-canvas-size <PX>    Canvas when a request omits it [env: ZKBG_DEFAULT_CANVAS_SIZE, default: 420]
+  --allowed-origin <ORIGIN>     CORS origin, repeatable   [env: ZKBG_ALLOWED_ORIGINS (comma-separated), default: *]
+  --gas-source <NAME>           etherscan|json-rpc|static|mock [env: ZKBG_GAS_SOURCE, default: etherscan with a key, else json-rpc]
+  --price-source <NAME>         coingecko|static|mock     [env: ZKBG_PRICE_SOURCE, default: coingecko]
+  --price-file <FILE>           File for the static source [env: ZKBG_PRICE_FILE, default: prices.json]
+  --rpc-url <URL>               Node for the json-rpc source [env: ZKBG_RPC_URL, default: http://127.0.0.1:8545]
+  --price-cache-ttl <SECS>      Reuse quotes this long, 0 = off [env: ZKBG_PRICE_CACHE_TTL, default: 60]
+  --price-refresh <SECS>        Background refresh, 0 = off [env: ZKBG_PRICE_REFRESH, default: 30]
+  --etherscan-api-key <KEY>     Etherscan API key         [env: ETHERSCAN_API_KEY]
+  -h, --help                    Print this help";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: String,
+    pub static_dir: PathBuf,
+    pub keys_dir: PathBuf,
+    pub artifact_path: PathBuf, // read once at startup; gas simulation is unavailable without it
+    pub default_canvas_size: u64,
+    pub allowed_origins: Vec<String>,
+    pub prices: PriceConfig,
+    pub api_keys: ApiKeys,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind: "127.0.0.1:3030".to_string(),
+            static_dir: PathBuf::from("static"),
+            keys_dir: PathBuf::from("keys"),
+            artifact_path: PathBuf::from(DEFAULT_ARTIFACT_PATH),
+            default_canvas_size: DEFAULT_CANVAS_SIZE,
+            allowed_origins: vec!["*".to_string()],
+            prices: PriceConfig::default(),
+            api_keys: ApiKeys::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriceConfig {
+    pub gas_source: Option<String>, // None: etherscan if a key is configured, else json-rpc
+    pub usd_source: String,
+    pub static_file: PathBuf,
+    pub rpc_url: String,
+    pub cache_ttl_secs: u64,        // 0 disables the price cache
+    pub refresh_interval_secs: u64, // 0 disables background refresh
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        PriceConfig {
+            gas_source: None,
+            usd_source: "coingecko".to_string(),
+            static_file: PathBuf::from("prices.json"),
+            rpc_url: "http://127.0.0.1:8545".to_string(),
+            cache_ttl_secs: 60,
+            refresh_interval_secs: 30,
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiKeys {
+    pub etherscan: Option<String>,
+}
+
+// Keys never end up in logs
+impl fmt::Debug for ApiKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeys")
+            .field("etherscan", &self.etherscan.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+// `--flag value` / `--flag=value` pairs in command-line order
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub help: bool,
+    pub flags: Vec<(String, String)>,
+}
+
+pub fn parse_cli(args: impl IntoIterator<Item = String>) -> Result<CliArgs> {
+    let mut cli = CliArgs::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            cli.help = true;
+            continue;
+        }
+        let flag = arg.strip_prefix("--").ok_or_else(|| anyhow!("unexpected argument '{}'", arg))?;
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => {
+                let value = args.next().ok_or_else(|| anyhow!("--{} needs a value", flag))?;
+                (flag.to_string(), value)
+            }
+        };
+        cli.flags.push((name, value));
+    }
+    Ok(cli)
+}
+
+impl ServerConfig {
+    // Full layering with the process environment
+    pub fn load(cli: &CliArgs) -> Result<Self> {
+        Self::load_with_env(cli, |name| std::env::var(name).ok())
+    }
+
+    pub fn load_with_env(cli: &CliArgs, env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let explicit_file = cli.flags.iter()
+            .rev()
+            .find(|(name, _)| name == "config")
+            .map(|(_, value)| PathBuf::from(value))
+            .or_else(|| env("ZKBG_CONFIG").map(PathBuf::from));
+
+        let mut config = match explicit_file {
+            Some(path) => Self::from_toml_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_toml_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => ServerConfig::default(),
+        };
+
+        let env_settings = [
+            ("ZKBG_BIND", "bind"),
+            ("ZKBG_STATIC_DIR", "static-dir"),
+            ("ZKBG_KEYS_DIR", "keys-dir"),
+            ("ZKBG_ARTIFACT_PATH", "artifact-path"),
+            ("ZKBG_DEFAULT_CANVAS_SIZE", "default-canvas-size"),
+            ("ZKBG_ALLOWED_ORIGINS", "allowed-origins"),
+            ("ZKBG_GAS_SOURCE", "gas-source"),
+            ("ZKBG_PRICE_SOURCE", "price-source"),
+            ("ZKBG_PRICE_FILE", "price-file"),
+            ("ZKBG_RPC_URL", "rpc-url"),
+            ("ZKBG_PRICE_CACHE_TTL", "price-cache-ttl"),
+            ("ZKBG_PRICE_REFRESH", "price-refresh"),
+            ("ETHERSCAN_API_KEY", "etherscan-api-key"),
+        ];
+        for (variable, setting) in env_settings {
+            if let Some(value) = env(variable) {
+                config.apply(setting, &value).with_context(|| format!("in ${}", variable))?;
+            }
+        }
+
+        // Repeated --allowed-origin flags accumulate rather than override each other
+        let mut cli_origins = Vec::new();
+        for (name, value) in &cli.flags {
+            match name.as_str() {
+                "config" => {}
+                "allowed-origin" => cli_origins.push(value.trim().to_string()),
+                _ => config.apply(name, value).with_context(|| format!("in --{}", name))?,
+            }
+        }
+        if !cli_origins.is_empty() {
+            config.allowed_origins = cli_origins;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_toml_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    // One setting from the environment or the command line
+    fn apply(&mut self, setting: &str, value: &str) -> Result<()> {
+        match setting {
+            "bind" => self.bind = value.to_string(),
+            "static-dir" => self.static_dir = PathBuf::from(value),
+            "keys-dir" => self.keys_dir = PathBuf::from(value),
+            "artifact-path" => self.artifact_path = PathBuf::from(value),
+            "default-canvas-size" => {
+                self.default_canvas_size = value.parse().with_context(|| format!("'{}' is not a canvas size", value))?
+            }
+            "allowed-origins" => {
+                self.allowed_origins = value.split(',').map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect()
+            }
+            "gas-source" => self.prices.gas_source = Some(value.to_string()),
+            "price-source" => self.prices.usd_source = value.to_string(),
+            "price-file" => self.prices.static_file = PathBuf::from(value),
+            "rpc-url" => self.prices.rpc_url = value.to_string(),
+            "price-cache-ttl" => {
+                self.prices.cache_ttl_secs = value.parse().with_context(|| format!("'{}' is not a number of seconds", value))?
+            }
+            "price-refresh" => {
+                self.prices.refresh_interval_secs =
+                    value.parse().with_context(|| format!("'{}' is not a number of seconds", value))?
+            }
+            "etherscan-api-key" => self.api_keys.etherscan = Some(value.to_string()).filter(|key| !key.is_empty()),
+            other => bail!("unknown setting '{}' (see --help)", other),
+        }
+        Ok(())
+    }
+
+    pub fn bind_addr(&self) -> Result<SocketAddr> {
+        self.bind.parse().with_context(|| format!("bind address '{}' is not host:port", self.bind))
+    }
+
+    pub fn gas_source(&self) -> &str {
+        match &self.prices.gas_source {
+            Some(source) => source,
+            None if self.api_keys.etherscan.is_some() => "etherscan",
+            None => "json-rpc",
+        }
+    }
+
+    pub fn price_source_options(&self) -> PriceSourceOptions {
+        PriceSourceOptions {
+            etherscan_api_key: self.api_keys.etherscan.clone(),
+            rpc_url: self.prices.rpc_url.clone(),
+            static_file: self.prices.static_file.clone(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.bind_addr()?;
+
+        if !self.static_dir.is_dir() {
+            bail!("static_dir {} is not a directory", self.static_dir.display());
+        }
+
+        if !(MIN_CANVAS_SIZE..=MAX_CANVAS_SIZE).contains(&self.default_canvas_size) {
+            bail!(
+                "default_canvas_size {} is outside {}..={}",
+                self.default_canvas_size, MIN_CANVAS_SIZE, MAX_CANVAS_SIZE
+            );
+        }
+
+        if self.allowed_origins.is_empty() {
+            bail!("allowed_origins is empty; use [\"*\"] to allow any origin");
+        }
+        for origin in &self.allowed_origins {
+            let host = origin.strip_prefix("https://").or_else(|| origin.strip_prefix("http://"));
+            let valid = origin == "*" || host.is_some_and(|host| !host.is_empty() && !host.contains('/'));
+            if !valid {
+                bail!("allowed origin '{}' must be '*' or scheme://host[:port]", origin);
+            }
+        }
+
+        let gas_source = self.gas_source();
+        for (kind, source) in [("gas", gas_source), ("price", self.prices.usd_source.as_str())] {
+            if !PRICE_SOURCES.contains(&source) {
+                bail!("unknown {} source '{}' (one of {})", kind, source, PRICE_SOURCES.join(", "));
+            }
+        }
+        if gas_source == "coingecko" {
+            bail!("coingecko has no gas prices; pick etherscan, json-rpc, static or mock");
+        }
+        if gas_source == "etherscan" && self.api_keys.etherscan.is_none() {
+            bail!("gas source etherscan needs an API key (ETHERSCAN_API_KEY or --etherscan-api-key)");
+        }
+        if matches!(self.prices.usd_source.as_str(), "etherscan" | "json-rpc" | "rpc") {
+            bail!("{} has no USD prices; pick coingecko, static or mock", self.prices.usd_source);
+        }
+
+        if self.prices.refresh_interval_secs > 0 && self.prices.cache_ttl_secs == 0 {
+            bail!("price refresh_interval_secs needs a cache_ttl_secs to refresh into");
+        }
+
+        Ok(())
+    }
+
+    pub fn price_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.prices.cache_ttl_secs)
+    }
+
+    // None when background refresh is off
+    pub fn price_refresh_interval(&self) -> Option<Duration> {
+        Some(self.prices.refresh_interval_secs).filter(|&secs| secs > 0).map(Duration::from_secs)
+    }
+}
@@ -0,0 +1,265 @@
+use sha3::{Digest, Keccak256};
+
+use crate::api::TriangleData;
+use crate::generation::Galaxy;
+
+// Mirrors of zkBgNFT.sol constants
+pub const PACKED_TRIANGLE_BYTES: usize = 10;
+pub const MAX_TRIANGLES_PER_BATCH: usize = 184;
+pub const CONTRACT_CANVAS_SIZE: u64 = 420;
+
+// Opacity byte the visualizer has always minted with (the contract accepts 0-100)
+pub const DEFAULT_TRIANGLE_OPACITY: u8 = 80;
+
+// One addTriangleBatch record: x1,y1,x2,y2,x3,y3, arm, index, type, opacity
+pub fn pack_triangle(triangle: &TriangleData, opacity: u8) -> [u8; PACKED_TRIANGLE_BYTES] {
+    let [(x1, y1), (x2, y2), (x3, y3)] = triangle.packed_vertices;
+
+    [
+        x1,
+        y1,
+        x2,
+        y2,
+        x3,
+        y3,
+        triangle.arm_index as u8,
+        triangle.triangle_index as u8,
+        triangle.triangle_type as u8,
+        opacity,
+    ]
+}
+
+// One decoded record, as _processTriangleAtIndex reads it back out of calldata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnpackedTriangle {
+    pub packed_vertices: [(u8, u8); 3],
+    pub arm_index: u8,
+    pub triangle_index: u8,
+    pub triangle_type: u8,
+    pub opacity: u8,
+}
+
+impl UnpackedTriangle {
+    // Same checks as _isValidPackedTriangle
+    pub fn is_valid(&self) -> bool {
+        let [a, b, c] = self.packed_vertices;
+        self.triangle_type <= 3 && self.opacity <= 100 && !(a == b && b == c) && self.arm_index <= 7
+    }
+}
+
+pub fn unpack_triangle(record: &[u8; PACKED_TRIANGLE_BYTES]) -> UnpackedTriangle {
+    UnpackedTriangle {
+        packed_vertices: [(record[0], record[1]), (record[2], record[3]), (record[4], record[5])],
+        arm_index: record[6],
+        triangle_index: record[7],
+        triangle_type: record[8],
+        opacity: record[9],
+    }
+}
+
+// Decode an addTriangleBatch blob. Like the contract, trailing bytes short of a record are ignored.
+pub fn unpack_triangles(packed: &[u8]) -> Vec<UnpackedTriangle> {
+    packed.chunks_exact(PACKED_TRIANGLE_BYTES)
+        .map(|record| unpack_triangle(record.try_into().expect("chunks_exact yields full records")))
+        .collect()
+}
+
+// The `bytes` blob addTriangleBatch / mintSpiral decode, 10 bytes per triangle
+pub fn pack_triangles(triangles: &[TriangleData], opacity: u8) -> Vec<u8> {
+    triangles.iter()
+        .flat_map(|triangle| pack_triangle(triangle, opacity))
+        .collect()
+}
+
+// packTriangleData's storage word: the record in the top 10 bytes, 22 zero bytes below
+pub fn triangle_storage_word(record: &[u8; PACKED_TRIANGLE_BYTES]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[..PACKED_TRIANGLE_BYTES].copy_from_slice(record);
+    word
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+// keccak256(packedTriangles): the calldata blob as submitted
+pub fn packed_triangles_hash(triangles: &[TriangleData], opacity: u8) -> [u8; 32] {
+    keccak256(&pack_triangles(triangles, opacity))
+}
+
+// keccak256(abi.encodePacked(packedTriangleData[tokenId][i].data for each i)): what storage holds after minting
+pub fn stored_triangles_hash(triangles: &[TriangleData], opacity: u8) -> [u8; 32] {
+    let words: Vec<u8> = triangles.iter()
+        .flat_map(|triangle| triangle_storage_word(&pack_triangle(triangle, opacity)))
+        .collect();
+    keccak256(&words)
+}
+
+pub const INITIALIZE_GALAXY_SIGNATURE: &str =
+    "initializeGalaxy(address,(uint64,uint64,uint8,uint8,uint8,uint64,uint64,uint64),bytes32,string,uint16)";
+pub const ADD_TRIANGLE_BATCH_SIGNATURE: &str = "addTriangleBatch(uint256,bytes,bool)";
+pub const MINT_SPIRAL_SIGNATURE: &str =
+    "mintSpiral(address,(uint64,uint64,uint8,uint8,uint8,uint64,uint64,uint64),bytes,bytes32,string)";
+
+// The subset of Solidity ABI types the zkBgNFT entry points take
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiToken {
+    Address([u8; 20]),
+    Uint(u64),
+    Bool(bool),
+    FixedBytes32([u8; 32]),
+    Bytes(Vec<u8>),
+    String(String),
+    Tuple(Vec<AbiToken>), // Static members only (SpiralConfig)
+}
+
+impl AbiToken {
+    fn head_words(&self) -> usize {
+        match self {
+            AbiToken::Tuple(members) => members.iter().map(AbiToken::head_words).sum(),
+            _ => 1,
+        }
+    }
+}
+
+fn uint_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn encode_static(token: &AbiToken, out: &mut Vec<u8>) {
+    match token {
+        AbiToken::Address(address) => {
+            out.extend_from_slice(&[0u8; 12]);
+            out.extend_from_slice(address);
+        }
+        AbiToken::Uint(value) => out.extend_from_slice(&uint_word(*value)),
+        AbiToken::Bool(value) => out.extend_from_slice(&uint_word(*value as u64)),
+        AbiToken::FixedBytes32(bytes) => out.extend_from_slice(bytes),
+        AbiToken::Tuple(members) => members.iter().for_each(|member| encode_static(member, out)),
+        AbiToken::Bytes(_) | AbiToken::String(_) => unreachable!("dynamic tokens (or tuples holding them) aren't encoded inline"),
+    }
+}
+
+// Length word followed by the data right-padded to a 32-byte boundary
+fn encode_dynamic(data: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&uint_word(data.len() as u64));
+    out.extend_from_slice(data);
+    out.resize(out.len() + (32 - data.len() % 32) % 32, 0);
+}
+
+// Standard head/tail encoding of a function's argument list
+pub fn encode_arguments(tokens: &[AbiToken]) -> Vec<u8> {
+    let head_size = tokens.iter().map(AbiToken::head_words).sum::<usize>() * 32;
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+
+    for token in tokens {
+        match token {
+            AbiToken::Bytes(data) => {
+                head.extend_from_slice(&uint_word((head_size + tail.len()) as u64));
+                encode_dynamic(data, &mut tail);
+            }
+            AbiToken::String(text) => {
+                head.extend_from_slice(&uint_word((head_size + tail.len()) as u64));
+                encode_dynamic(text.as_bytes(), &mut tail);
+            }
+            _ => encode_static(token, &mut head),
+        }
+    }
+
+    head.extend_from_slice(&tail);
+    head
+}
+
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+pub fn encode_call(signature: &str, tokens: &[AbiToken]) -> Vec<u8> {
+    let mut calldata = function_selector(signature).to_vec();
+    calldata.extend(encode_arguments(tokens));
+    calldata
+}
+
+// SpiralConfig tuple in field order. backgroundType is sent as generated; the contract recomputes it anyway.
+pub fn spiral_config_token(galaxy: &Galaxy) -> AbiToken {
+    AbiToken::Tuple(vec![
+        AbiToken::Uint(galaxy.seed),
+        AbiToken::Uint(galaxy.variant),
+        AbiToken::Uint(galaxy.spiral_type),
+        AbiToken::Uint(galaxy.num_arms),
+        AbiToken::Uint(galaxy.background_type),
+        AbiToken::Uint(galaxy.spiral_quotient),
+        AbiToken::Uint(galaxy.arms_quotient),
+        AbiToken::Uint(galaxy.arms_remainder),
+    ])
+}
+
+pub fn initialize_galaxy_calldata(
+    to: [u8; 20],
+    galaxy: &Galaxy,
+    zk_proof_hash: [u8; 32],
+    uri: &str,
+    expected_triangles: u64,
+) -> Vec<u8> {
+    encode_call(
+        INITIALIZE_GALAXY_SIGNATURE,
+        &[
+            AbiToken::Address(to),
+            spiral_config_token(galaxy),
+            AbiToken::FixedBytes32(zk_proof_hash),
+            AbiToken::String(uri.to_string()),
+            AbiToken::Uint(expected_triangles),
+        ],
+    )
+}
+
+pub fn add_triangle_batch_calldata(token_id: u64, packed_triangles: &[u8], is_last_batch: bool) -> Vec<u8> {
+    encode_call(
+        ADD_TRIANGLE_BATCH_SIGNATURE,
+        &[
+            AbiToken::Uint(token_id),
+            AbiToken::Bytes(packed_triangles.to_vec()),
+            AbiToken::Bool(is_last_batch),
+        ],
+    )
+}
+
+pub fn mint_spiral_calldata(
+    to: [u8; 20],
+    galaxy: &Galaxy,
+    packed_triangles: &[u8],
+    zk_proof_hash: [u8; 32],
+    uri: &str,
+) -> Vec<u8> {
+    encode_call(
+        MINT_SPIRAL_SIGNATURE,
+        &[
+            AbiToken::Address(to),
+            spiral_config_token(galaxy),
+            AbiToken::Bytes(packed_triangles.to_vec()),
+            AbiToken::FixedBytes32(zk_proof_hash),
+            AbiToken::String(uri.to_string()),
+        ],
+    )
+}
+
+// Split the packed blob into addTriangleBatch payloads of at most batch_size triangles
+pub fn triangle_batches(packed_triangles: &[u8], batch_size: usize) -> Vec<&[u8]> {
+    let batch_size = batch_size.clamp(1, MAX_TRIANGLES_PER_BATCH);
+    packed_triangles.chunks(batch_size * PACKED_TRIANGLE_BYTES).collect()
+}
+
+// "0x"-prefixed 20-byte address
+pub fn parse_address(address: &str) -> Option<[u8; 20]> {
+    let digits = address.strip_prefix("0x").unwrap_or(address);
+    hex::decode(digits).ok()?.try_into().ok()
+}
+
+pub fn parse_bytes32(value: &str) -> Option<[u8; 32]> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(digits).ok()?.try_into().ok()
+}
@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{Address, Bytes, ExecutionResult, Output, TransactTo, U256},
+    EVM,
+};
+use serde::Serialize;
+
+use crate::api::spiral_response;
+use crate::contract::{
+    add_triangle_batch_calldata, initialize_galaxy_calldata, keccak256, pack_triangles, triangle_batches,
+    DEFAULT_TRIANGLE_OPACITY, MAX_TRIANGLES_PER_BATCH,
+};
+use crate::generation::Galaxy;
+
+// Written by `npx hardhat compile` in blockchain/
+pub const DEFAULT_ARTIFACT_PATH: &str = "blockchain/artifacts/contracts/zkBgNFT.sol/zkBgNFT.json";
+
+// Same per-transaction ceiling the hardhat network is configured with
+pub const SIMULATION_GAS_LIMIT: u64 = 30_000_000;
+
+// Deployer, minter and token owner in every simulation
+const SIMULATION_CALLER: [u8; 20] = [0x11; 20];
+
+// Creation bytecode from a hardhat artifact's "bytecode" field
+pub fn load_artifact_bytecode(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    let artifact: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(path).with_context(|| format!("reading {} (run `npx hardhat compile`)", path.display()))?,
+    )?;
+    let bytecode = artifact["bytecode"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} has no bytecode field", path.display()))?;
+    Ok(hex::decode(bytecode.trim_start_matches("0x"))?)
+}
+
+// Artifact bytecode read once at startup, or why it couldn't be
+#[derive(Debug, Clone)]
+pub struct ContractArtifact {
+    pub path: PathBuf,
+    bytecode: Result<Arc<Vec<u8>>, String>,
+}
+
+impl ContractArtifact {
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let bytecode = load_artifact_bytecode(&path).map(Arc::new).map_err(|e| format!("{:#}", e));
+        ContractArtifact { path, bytecode }
+    }
+
+    pub fn bytecode(&self) -> Result<Arc<Vec<u8>>, String> {
+        self.bytecode.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CallOutcome {
+    pub success: bool,
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+    pub revert_reason: Option<String>,
+}
+
+// Error(string) payload from a revert, if that's what it is
+fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    let selector = &keccak256(b"Error(string)")[..4];
+    if output.len() < 68 || &output[..4] != selector {
+        return None;
+    }
+    let length = u64::from_be_bytes(output[60..68].try_into().ok()?) as usize;
+    let text = output.get(68..68 + length)?;
+    Some(String::from_utf8_lossy(text).into_owned())
+}
+
+// In-memory EVM with no network or fork: every account starts empty
+pub struct EvmSimulator {
+    evm: EVM<CacheDB<EmptyDB>>,
+    caller: Address,
+}
+
+impl Default for EvmSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EvmSimulator {
+    pub fn new() -> Self {
+        let mut evm = EVM::new();
+        evm.database(CacheDB::new(EmptyDB::default()));
+        // zkBgNFT is deployed with allowUnlimitedContractSize, so skip the EIP-170 limit
+        evm.env.cfg.limit_contract_code_size = Some(usize::MAX);
+        evm.env.tx.gas_price = U256::ZERO;
+        evm.env.tx.gas_limit = SIMULATION_GAS_LIMIT;
+
+        EvmSimulator {
+            evm,
+            caller: Address::from(SIMULATION_CALLER),
+        }
+    }
+
+    pub fn caller(&self) -> [u8; 20] {
+        SIMULATION_CALLER
+    }
+
+    fn transact(&mut self, transact_to: TransactTo, data: Vec<u8>) -> Result<(CallOutcome, Option<Address>)> {
+        self.evm.env.tx.caller = self.caller;
+        self.evm.env.tx.transact_to = transact_to;
+        self.evm.env.tx.data = Bytes::from(data);
+        self.evm.env.tx.value = U256::ZERO;
+
+        let result = self.evm.transact_commit().map_err(|e| anyhow!("EVM error: {:?}", e))?;
+        Ok(match result {
+            ExecutionResult::Success { gas_used, output, .. } => {
+                let (output, created) = match output {
+                    Output::Call(bytes) => (bytes.to_vec(), None),
+                    Output::Create(bytes, address) => (bytes.to_vec(), address),
+                };
+                (CallOutcome { success: true, gas_used, output, revert_reason: None }, created)
+            }
+            ExecutionResult::Revert { gas_used, output } => {
+                let revert_reason = decode_revert_reason(&output);
+                (CallOutcome { success: false, gas_used, output: output.to_vec(), revert_reason }, None)
+            }
+            ExecutionResult::Halt { reason, gas_used } => (
+                CallOutcome {
+                    success: false,
+                    gas_used,
+                    output: Vec::new(),
+                    revert_reason: Some(format!("halted: {:?}", reason)),
+                },
+                None,
+            ),
+        })
+    }
+
+    // Run creation code; returns the new contract address and the deployment gas
+    pub fn deploy(&mut self, init_code: Vec<u8>) -> Result<(Address, u64)> {
+        let (outcome, created) = self.transact(TransactTo::create(), init_code)?;
+        match created {
+            Some(address) if outcome.success => Ok((address, outcome.gas_used)),
+            _ => bail!("deployment failed: {}", outcome.revert_reason.unwrap_or_else(|| "no address".to_string())),
+        }
+    }
+
+    pub fn call(&mut self, to: Address, calldata: Vec<u8>) -> Result<CallOutcome> {
+        Ok(self.transact(TransactTo::Call(to), calldata)?.0)
+    }
+}
+
+// Measured gas for the two-step mint of one galaxy on a fresh zkBgNFT deployment
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedGas {
+    pub seed: u64,
+    pub triangle_count: u64,
+    pub deploy_gas: u64,
+    pub initialize_galaxy_gas: u64,
+    pub add_triangle_batch_gas: Vec<u64>,
+    pub total_mint_gas: u64, // initializeGalaxy + every addTriangleBatch
+}
+
+pub fn simulate_mint_gas(bytecode: &[u8], galaxy: &Galaxy, batch_size: usize) -> Result<SimulatedGas> {
+    let mut sim = EvmSimulator::new();
+    let (contract, deploy_gas) = sim.deploy(bytecode.to_vec())?;
+
+    let triangles = spiral_response(galaxy).triangles;
+    let packed = pack_triangles(&triangles, DEFAULT_TRIANGLE_OPACITY);
+    let owner = sim.caller();
+
+    let initialize = sim.call(
+        contract,
+        initialize_galaxy_calldata(owner, galaxy, keccak256(&packed), "", triangles.len() as u64),
+    )?;
+    if !initialize.success {
+        bail!("initializeGalaxy reverted: {}", initialize.revert_reason.unwrap_or_default());
+    }
+
+    // First token on a fresh deployment
+    let token_id = 0;
+    let batches = triangle_batches(&packed, batch_size.min(MAX_TRIANGLES_PER_BATCH));
+    let mut add_triangle_batch_gas = Vec::with_capacity(batches.len());
+    for (i, batch) in batches.iter().enumerate() {
+        let is_last = i + 1 == batches.len();
+        let outcome = sim.call(contract, add_triangle_batch_calldata(token_id, batch, is_last))?;
+        if !outcome.success {
+            bail!("addTriangleBatch {} reverted: {}", i, outcome.revert_reason.unwrap_or_default());
+        }
+        add_triangle_batch_gas.push(outcome.gas_used);
+    }
+
+    Ok(SimulatedGas {
+        seed: galaxy.seed,
+        triangle_count: triangles.len() as u64,
+        deploy_gas,
+        initialize_galaxy_gas: initialize.gas_used,
+        total_mint_gas: initialize.gas_used + add_triangle_batch_gas.iter().sum::<u64>(),
+        add_triangle_batch_gas,
+    })
+}
@@ -0,0 +1,151 @@
+use serde::Serialize;
+
+use crate::api::unix_timestamp;
+use crate::evm_sim::SimulatedGas;
+use crate::generation::Galaxy;
+use crate::prices::{PriceCache, SourceReport, UsdPrices};
+
+// Fallbacks used when the price APIs are unreachable
+pub const FALLBACK_GAS_PRICE_GWEI: f64 = 20.0;
+pub const FALLBACK_ETH_PRICE_USD: f64 = 3000.0;
+pub const FALLBACK_BTC_PRICE_USD: f64 = 45000.0;
+
+#[derive(Serialize)]
+pub struct GasResponse {
+    pub gas_price_gwei: f64,
+    pub estimated_gas_units: u64,
+    pub total_gas_cost_eth: f64,
+    pub total_gas_cost_usd: f64,
+    pub total_gas_cost_btc: f64,
+    pub eth_price_usd: f64,
+    pub btc_price_usd: f64,
+    pub breakdown: GasBreakdown,
+    pub optimization_savings: OptimizationSavings,
+    pub gas_units_source: SourceReport, // "evm-simulation", or the constant fallback when unmeasured
+    pub gas_price_source: SourceReport,
+    pub usd_price_source: SourceReport,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+pub struct GasBreakdown {
+    pub base_mint_gas: u64,
+    pub zk_proof_gas: u64,
+    pub metadata_gas: u64,
+    pub triangle_storage_gas: u64,
+    pub total_gas: u64,
+}
+
+#[derive(Serialize)]
+pub struct OptimizationSavings {
+    pub triangle_reduction_savings: u64,
+    pub data_packing_savings: u64,
+    pub total_savings: u64,
+    pub original_estimate: u64,
+    pub optimized_estimate: u64,
+    pub savings_percentage: f64,
+}
+
+pub fn estimate_optimized_gas(galaxy: &Galaxy) -> (GasBreakdown, OptimizationSavings) {
+    // OPTIMIZED gas costs
+    let base_mint_gas = 50000u64;
+    let zk_proof_gas = 100000u64; // Reduced complexity
+
+    // OPTIMIZED: Calculate gas for 23 triangles instead of 69
+    let triangle_count = galaxy.triangles.len() as u64;
+    let triangle_storage_gas = triangle_count * 5000; // Reduced per triangle due to packing
+
+    let metadata_gas = 30000u64; // Reduced metadata complexity
+
+    let total_optimized = base_mint_gas + zk_proof_gas + triangle_storage_gas + metadata_gas;
+
+    let breakdown = GasBreakdown {
+        base_mint_gas,
+        zk_proof_gas,
+        metadata_gas,
+        triangle_storage_gas,
+        total_gas: total_optimized,
+    };
+
+    let savings = optimization_savings(galaxy, &breakdown);
+    (breakdown, savings)
+}
+
+// Breakdown from a revm run of the contract: initializeGalaxy mints and stores the metadata,
+// the addTriangleBatch calls store the triangles, and no proof is verified on-chain
+pub fn measured_gas_breakdown(simulated: &SimulatedGas) -> GasBreakdown {
+    GasBreakdown {
+        base_mint_gas: simulated.initialize_galaxy_gas,
+        zk_proof_gas: 0,
+        metadata_gas: 0,
+        triangle_storage_gas: simulated.add_triangle_batch_gas.iter().sum(),
+        total_gas: simulated.total_mint_gas,
+    }
+}
+
+// Savings compared to the original 69-triangle approach
+fn optimization_savings(galaxy: &Galaxy, breakdown: &GasBreakdown) -> OptimizationSavings {
+    let original_triangle_count = galaxy.num_arms * 69;
+    let original_storage_gas = original_triangle_count * 20000; // Original gas per triangle
+    let original_total = 50000 + 120000 + original_storage_gas + 50000;
+
+    let triangle_reduction_savings = original_storage_gas.saturating_sub(breakdown.triangle_storage_gas);
+    let data_packing_savings = 50000; // Estimated savings from uint8 packing
+    let total_savings = triangle_reduction_savings + data_packing_savings;
+
+    OptimizationSavings {
+        triangle_reduction_savings,
+        data_packing_savings,
+        total_savings,
+        original_estimate: original_total,
+        optimized_estimate: breakdown.total_gas,
+        savings_percentage: (total_savings as f64 / original_total as f64) * 100.0,
+    }
+}
+
+// `simulated` is the revm measurement for this galaxy, or why there isn't one (no artifact,
+// a revert); then the constant estimate is used and flagged as a fallback
+pub async fn calculate_gas_costs(
+    galaxy: &Galaxy,
+    prices: &PriceCache,
+    simulated: Result<SimulatedGas, String>,
+) -> GasResponse {
+    let ((gas_price_gwei, gas_price_source), (usd, usd_price_source)) =
+        tokio::join!(prices.gas_price_gwei(), prices.usd_prices());
+    let UsdPrices { eth: eth_price_usd, btc: btc_price_usd } = usd;
+
+    let (breakdown, optimization_savings, gas_units_source) = match simulated {
+        Ok(simulated) => {
+            let breakdown = measured_gas_breakdown(&simulated);
+            let savings = optimization_savings(galaxy, &breakdown);
+            let report = SourceReport { source: "evm-simulation".to_string(), fallback: false, error: None, age_ms: 0 };
+            (breakdown, savings, report)
+        }
+        Err(error) => {
+            let (breakdown, savings) = estimate_optimized_gas(galaxy);
+            let report = SourceReport { source: "constant-estimate".to_string(), fallback: true, error: Some(error), age_ms: 0 };
+            (breakdown, savings, report)
+        }
+    };
+
+    let gas_price_eth = gas_price_gwei / 1_000_000_000.0;
+    let total_gas_cost_eth = (breakdown.total_gas as f64) * gas_price_eth;
+    let total_gas_cost_usd = total_gas_cost_eth * eth_price_usd;
+    let total_gas_cost_btc = total_gas_cost_usd / btc_price_usd;
+
+    GasResponse {
+        gas_price_gwei,
+        estimated_gas_units: breakdown.total_gas,
+        total_gas_cost_eth,
+        total_gas_cost_usd,
+        total_gas_cost_btc,
+        eth_price_usd,
+        btc_price_usd,
+        breakdown,
+        optimization_savings,
+        gas_units_source,
+        gas_price_source,
+        usd_price_source,
+        timestamp: unix_timestamp(),
+    }
+}
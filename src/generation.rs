@@ -0,0 +1,334 @@
+// Spiral geometry shared by the API, the renderers and the circuit witnesses/lookup tables
+pub const PARTICLES_PER_ARM: u64 = 23;
+pub const MAX_RADIUS_RATIO: u64 = 4963; // max_radius = canvas_size * 4963 / 10000
+pub const TRIG_TABLE_SIZE: usize = 32;
+pub const SCALE_FACTOR: i64 = 10000;
+
+pub const SIN_TABLE: [i64; TRIG_TABLE_SIZE] = [
+    0, 1951, 3827, 5556, 7071, 8315, 9239, 9808,
+    10000, 9808, 9239, 8315, 7071, 5556, 3827, 1951,
+    0, -1951, -3827, -5556, -7071, -8315, -9239, -9808,
+    -10000, -9808, -9239, -8315, -7071, -5556, -3827, -1951,
+];
+
+pub const COS_TABLE: [i64; TRIG_TABLE_SIZE] = [
+    10000, 9808, 9239, 8315, 7071, 5556, 3827, 1951,
+    0, -1951, -3827, -5556, -7071, -8315, -9239, -9808,
+    -10000, -9808, -9239, -8315, -7071, -5556, -3827, -1951,
+    0, 1951, 3827, 5556, 7071, 8315, 9239, 9808,
+];
+
+// Helper function to calculate configuration mapping (unchanged)
+pub fn calculate_configuration_mapping(variant_id: u64) -> (u64, u64, u64, u64, u64) {
+    let spiral_type = variant_id % 3;
+    let spiral_quotient = variant_id / 3;
+    let arms_remainder = spiral_quotient % 6;
+    let arms_quotient = spiral_quotient / 6;
+    let num_arms = 3 + arms_remainder;
+
+    (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder)
+}
+
+// Helper function to generate background type from seed (UPDATED to 21 types)
+pub fn generate_background_type(seed: u64) -> u64 {
+    (seed / 7) % 21 // 0-20 for 21 background types
+}
+
+// One seed's complete galaxy: what the API serves and what the circuit witnesses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Galaxy {
+    pub seed: u64,
+    pub variant: u64,
+    pub quotient: u64,
+    pub canvas_size: u64,
+    pub spiral_type: u64,
+    pub num_arms: u64,
+    pub background_type: u64,
+    pub spiral_quotient: u64,
+    pub arms_quotient: u64,
+    pub arms_remainder: u64,
+    pub particles: Vec<Particle>,
+    pub triangles: Vec<Triangle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Particle {
+    pub x: u64,
+    pub y: u64,
+    pub arm_index: u64,
+    pub particle_index: u64,
+    pub angle_index: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Triangle {
+    pub vertices: [(u64, u64); 3],
+    pub arm_index: u64,
+    pub triangle_index: u64,
+    pub triangle_type: u64, // 0=spiral particle, 1=star, 2=core, 3=dust
+    pub size: u64,          // Triangle size for micro-triangle rendering
+}
+
+impl Galaxy {
+    // (x1, y1, x2, y2, x3, y3) per triangle, in circuit witness order
+    pub fn triangle_vertices(&self) -> Vec<(u64, u64, u64, u64, u64, u64)> {
+        self.triangles
+            .iter()
+            .map(|t| {
+                let [(x1, y1), (x2, y2), (x3, y3)] = t.vertices;
+                (x1, y1, x2, y2, x3, y3)
+            })
+            .collect()
+    }
+
+    // (arm_index, triangle_index, triangle_type) per triangle, in circuit witness order
+    pub fn triangle_metadata(&self) -> Vec<(u64, u64, u64)> {
+        self.triangles
+            .iter()
+            .map(|t| (t.arm_index, t.triangle_index, t.triangle_type))
+            .collect()
+    }
+}
+
+// Generate the full galaxy for a seed (the only entry point the server and prover should need)
+pub fn generate_galaxy(seed: u64, canvas_size: u64) -> Galaxy {
+    let variant = seed % 41;
+    let (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder) =
+        calculate_configuration_mapping(variant);
+
+    let (positions, metadata) = generate_spiral_particles(spiral_type, num_arms, canvas_size);
+    let (triangle_vertices, triangle_metadata) = generate_spiral_triangles(spiral_type, num_arms, canvas_size);
+
+    let particles = positions
+        .iter()
+        .zip(metadata.iter())
+        .map(|((x, y), (arm_index, particle_index, angle_index))| Particle {
+            x: *x,
+            y: *y,
+            arm_index: *arm_index,
+            particle_index: *particle_index,
+            angle_index: *angle_index,
+        })
+        .collect();
+
+    let triangles = triangle_vertices
+        .iter()
+        .zip(triangle_metadata.iter())
+        .map(|((x1, y1, x2, y2, x3, y3), (arm_index, triangle_index, triangle_type))| Triangle {
+            vertices: [(*x1, *y1), (*x2, *y2), (*x3, *y3)],
+            arm_index: *arm_index,
+            triangle_index: *triangle_index,
+            triangle_type: *triangle_type,
+            size: micro_triangle_size(*triangle_index),
+        })
+        .collect();
+
+    Galaxy {
+        seed,
+        variant,
+        quotient: seed / 41,
+        canvas_size,
+        spiral_type,
+        num_arms,
+        background_type: generate_background_type(seed),
+        spiral_quotient,
+        arms_quotient,
+        arms_remainder,
+        particles,
+        triangles,
+    }
+}
+
+// OPTIMIZED: Generate spiral particles (23 per arm instead of 69)
+pub fn generate_spiral_particles(
+    spiral_type: u64,
+    num_arms: u64,
+    canvas_size: u64,
+) -> (Vec<(u64, u64)>, Vec<(u64, u64, u64)>) {
+    let particles_per_arm = PARTICLES_PER_ARM; // OPTIMIZED: Reduced from 69
+    let mut positions = Vec::new();
+    let mut metadata = Vec::new();
+
+    for arm_index in 0..num_arms {
+        for particle_index in 0..particles_per_arm {
+            let (x, y, angle_index) = calculate_spiral_point(
+                arm_index,
+                particle_index,
+                num_arms,
+                spiral_type,
+                canvas_size,
+            );
+
+            positions.push((x, y));
+            metadata.push((arm_index, particle_index, angle_index));
+        }
+    }
+
+    (positions, metadata)
+}
+
+// OPTIMIZED: Generate micro-triangles for optimized particle effect
+pub fn generate_spiral_triangles(
+    spiral_type: u64,
+    num_arms: u64,
+    canvas_size: u64,
+) -> (Vec<(u64, u64, u64, u64, u64, u64)>, Vec<(u64, u64, u64)>) {
+    let (positions, _metadata) = generate_spiral_particles(spiral_type, num_arms, canvas_size);
+    let particles_per_arm = PARTICLES_PER_ARM; // OPTIMIZED: Reduced from 69
+
+    let mut triangle_vertices = Vec::new();
+    let mut triangle_metadata = Vec::new();
+
+    for arm_index in 0..num_arms {
+        for particle_index in 0..particles_per_arm {
+            let particle_idx = (arm_index * particles_per_arm + particle_index) as usize;
+
+            if particle_idx < positions.len() {
+                let (center_x, center_y) = positions[particle_idx];
+
+                // CREATE MICRO-TRIANGLES: Optimized size for gas efficiency
+                let triangle_size = micro_triangle_size(particle_index);
+
+                let (x1, y1, x2, y2, x3, y3) = create_micro_triangle(center_x, center_y, triangle_size);
+
+                triangle_vertices.push((x1, y1, x2, y2, x3, y3));
+                triangle_metadata.push((arm_index, particle_index, 0)); // Type 0 = spiral particle
+            }
+        }
+    }
+
+    (triangle_vertices, triangle_metadata)
+}
+
+// Micro-triangle size shrinks along the arm (6px at the core, minimum 2px)
+pub fn micro_triangle_size(particle_index: u64) -> u64 {
+    let base_size = 6u64;
+    let size_reduction = (particle_index * 3) / PARTICLES_PER_ARM; // Faster reduction for 23 particles
+    base_size.saturating_sub(size_reduction).max(2) // Minimum 2 pixels
+}
+
+// Create micro-triangles for optimized particle effect
+fn create_micro_triangle(center_x: u64, center_y: u64, size: u64) -> (u64, u64, u64, u64, u64, u64) {
+    let half_size = size / 2;
+
+    // Create small triangular "star" particle
+    let x1 = center_x;                    // Top point
+    let y1 = center_y.saturating_sub(half_size);
+    let x2 = center_x.saturating_sub(half_size); // Bottom left
+    let y2 = center_y + half_size;
+    let x3 = center_x + half_size;        // Bottom right
+    let y3 = center_y + half_size;
+
+    (x1, y1, x2, y2, x3, y3)
+}
+
+// Every intermediate value of calculate_spiral_point, as witnessed by the circuit.
+// Signed trig values are split into magnitude and sign so all witnesses stay small.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpiralPointTrace {
+    pub base_angle_index: u64,
+    pub t: u64,
+    pub progression: u64,
+    pub angle_wrap: u64,
+    pub angle_index: u64,
+    pub center: u64,
+    pub max_radius: u64,
+    pub radius: u64,
+    pub radius_remainder: u64,
+    pub sin_abs: u64,
+    pub sin_negative: u64,
+    pub cos_abs: u64,
+    pub cos_negative: u64,
+    pub dx: u64,
+    pub dx_remainder: u64,
+    pub dy: u64,
+    pub dy_remainder: u64,
+    pub x: u64,
+    pub y: u64,
+}
+
+pub(crate) fn base_angle_index(arm_index: u64, total_arms: u64) -> u64 {
+    (arm_index * TRIG_TABLE_SIZE as u64) / total_arms
+}
+
+// Position along the arm, 0..1000
+pub(crate) fn particle_progress(particle_index: u64) -> u64 {
+    (particle_index * 1000) / PARTICLES_PER_ARM
+}
+
+// OPTIMIZED SPIRAL TYPES: Enhanced distribution for 23 particles
+pub(crate) fn spiral_progression(spiral_type: u64, t: u64) -> u64 {
+    match spiral_type {
+        0 => (t * 18) / 1000, // Tight spiral - adjusted for 23 particles
+        1 => (t * 5) / 1000,  // Loose spiral - adjusted for 23 particles
+        _ => (t * 10) / 1000, // Classic spiral - adjusted for 23 particles
+    }
+}
+
+pub fn trace_spiral_point(
+    arm_index: u64,
+    particle_index: u64,
+    total_arms: u64,
+    spiral_type: u64,
+    canvas_size: u64,
+) -> SpiralPointTrace {
+    let max_radius = (canvas_size * MAX_RADIUS_RATIO) / SCALE_FACTOR as u64; // Keep same radius ratio
+
+    let base_angle_index = base_angle_index(arm_index, total_arms);
+    let t = particle_progress(particle_index);
+    let progression = spiral_progression(spiral_type, t);
+
+    let angle_sum = base_angle_index + progression;
+    let angle_index = angle_sum % (TRIG_TABLE_SIZE as u64);
+    let radius = (t * max_radius) / 1000;
+
+    let sin_val = SIN_TABLE[angle_index as usize];
+    let cos_val = COS_TABLE[angle_index as usize];
+
+    let center = canvas_size / 2;
+
+    // Integer division truncates toward zero, so |dx| = floor(radius * |cos| / 10000)
+    let dx_numerator = radius * cos_val.unsigned_abs();
+    let dy_numerator = radius * sin_val.unsigned_abs();
+    let dx = dx_numerator / SCALE_FACTOR as u64;
+    let dy = dy_numerator / SCALE_FACTOR as u64;
+
+    // FIXED: Remove .abs() and handle negative coordinates properly
+    let x = if cos_val < 0 { center.saturating_sub(dx) } else { center + dx };
+    let y = if sin_val < 0 { center.saturating_sub(dy) } else { center + dy };
+
+    SpiralPointTrace {
+        base_angle_index,
+        t,
+        progression,
+        angle_wrap: angle_sum / (TRIG_TABLE_SIZE as u64),
+        angle_index,
+        center,
+        max_radius,
+        radius,
+        radius_remainder: (t * max_radius) % 1000,
+        sin_abs: sin_val.unsigned_abs(),
+        sin_negative: (sin_val < 0) as u64,
+        cos_abs: cos_val.unsigned_abs(),
+        cos_negative: (cos_val < 0) as u64,
+        dx,
+        dx_remainder: dx_numerator % SCALE_FACTOR as u64,
+        dy,
+        dy_remainder: dy_numerator % SCALE_FACTOR as u64,
+        x,
+        y,
+    }
+}
+
+// FIXED: spiral point calculation with proper trigonometry
+pub fn calculate_spiral_point(
+    arm_index: u64,
+    particle_index: u64,
+    total_arms: u64,
+    spiral_type: u64,
+    canvas_size: u64,
+) -> (u64, u64, u64) {
+    let trace = trace_spiral_point(arm_index, particle_index, total_arms, spiral_type, canvas_size);
+
+    (trace.x, trace.y, trace.angle_index)
+}
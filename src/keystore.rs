@@ -0,0 +1,245 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use halo2_proofs::{
+    halo2curves::pasta::{EqAffine, Fp},
+    plonk::{Circuit, ConstraintSystem, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, ipa::commitment::ParamsIPA},
+    SerdeFormat,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::circuits::simple_spirals::{circuit_k, SimpleSpiralsCircuit, CIRCUIT_VERSION};
+use crate::prover::{CircuitKeys, VerifierKeys};
+
+// Raw points are decoded without curve checks, so every file is first checked against the
+// blake2b digest its manifest recorded. That catches truncated, corrupted or mixed-up files,
+// not someone able to rewrite the manifest as well.
+const KEY_FORMAT: SerdeFormat = SerdeFormat::RawBytes;
+
+// blake2b of each key file, as written by save()
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyFileDigests {
+    pub params: String,
+    pub vk: String,
+    pub pk: String,
+}
+
+// Written next to the key files; keys are only loaded when it matches the running circuit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyManifest {
+    pub circuit_version: u32,
+    pub k: u32,
+    pub num_arms: u64,
+    pub constraint_system_hash: String,
+    #[serde(default)]
+    pub files: KeyFileDigests,
+}
+
+impl KeyManifest {
+    // Manifest the current build would write for num_arms
+    pub fn current(num_arms: u64) -> Self {
+        KeyManifest {
+            circuit_version: CIRCUIT_VERSION,
+            k: circuit_k(num_arms),
+            num_arms,
+            constraint_system_hash: constraint_system_hash(),
+            files: KeyFileDigests::default(),
+        }
+    }
+
+    // Same circuit and shape, whatever the file digests
+    pub fn same_circuit(&self, other: &KeyManifest) -> bool {
+        self.circuit_version == other.circuit_version
+            && self.k == other.k
+            && self.num_arms == other.num_arms
+            && self.constraint_system_hash == other.constraint_system_hash
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyStoreError {
+    Missing,
+    Stale { found: KeyManifest, expected: KeyManifest },
+    Corrupt(PathBuf),
+    Io(io::Error),
+}
+
+impl fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyStoreError::Missing => write!(f, "no persisted keys"),
+            KeyStoreError::Stale { found, expected } => write!(
+                f,
+                "stale keys (version {}, k={}, cs {}) for current circuit (version {}, k={}, cs {})",
+                found.circuit_version,
+                found.k,
+                found.constraint_system_hash,
+                expected.circuit_version,
+                expected.k,
+                expected.constraint_system_hash
+            ),
+            KeyStoreError::Corrupt(path) => write!(f, "{} doesn't match its manifest digest", path.display()),
+            KeyStoreError::Io(e) => write!(f, "key files unreadable: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for KeyStoreError {}
+
+impl From<io::Error> for KeyStoreError {
+    fn from(e: io::Error) -> Self {
+        KeyStoreError::Io(e)
+    }
+}
+
+// blake2b of the pinned constraint system (gates, lookups, columns, permutation).
+// configure() doesn't depend on witnesses or the arm count, so this is one hash per build.
+pub fn constraint_system_hash() -> String {
+    let mut cs = ConstraintSystem::<Fp>::default();
+    SimpleSpiralsCircuit::<Fp>::configure(&mut cs);
+    let pinned = format!("{:?}", cs.pinned());
+    blake2b_hex(pinned.as_bytes())
+}
+
+fn blake2b_hex(bytes: &[u8]) -> String {
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .hash(bytes)
+        .to_hex()
+        .to_string()
+}
+
+// One directory holding params/vk/pk plus a manifest per arm count
+#[derive(Debug, Clone)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        KeyStore { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, num_arms: u64, extension: &str) -> PathBuf {
+        self.dir.join(format!("spirals_{}arms.{}", num_arms, extension))
+    }
+
+    pub fn manifest_path(&self, num_arms: u64) -> PathBuf {
+        self.path(num_arms, "manifest.json")
+    }
+
+    pub fn read_manifest(&self, num_arms: u64) -> Result<KeyManifest, KeyStoreError> {
+        let path = self.manifest_path(num_arms);
+        if !path.exists() {
+            return Err(KeyStoreError::Missing);
+        }
+        let manifest = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|e| KeyStoreError::Io(e.into()))?;
+        Ok(manifest)
+    }
+
+    // Manifest for num_arms, if it matches this build
+    fn current_manifest(&self, num_arms: u64) -> Result<KeyManifest, KeyStoreError> {
+        let found = self.read_manifest(num_arms)?;
+        let expected = KeyManifest::current(num_arms);
+        if !found.same_circuit(&expected) {
+            return Err(KeyStoreError::Stale { found, expected });
+        }
+        Ok(found)
+    }
+
+    // File contents, only if they hash to the digest the manifest recorded
+    fn read_checked(&self, num_arms: u64, extension: &str, digest: &str) -> Result<Vec<u8>, KeyStoreError> {
+        let path = self.path(num_arms, extension);
+        let bytes = fs::read(&path)?;
+        if blake2b_hex(&bytes) != digest {
+            return Err(KeyStoreError::Corrupt(path));
+        }
+        Ok(bytes)
+    }
+
+    fn load_params(&self, manifest: &KeyManifest) -> Result<ParamsIPA<EqAffine>, KeyStoreError> {
+        let bytes = self.read_checked(manifest.num_arms, "params", &manifest.files.params)?;
+        let params = ParamsIPA::<EqAffine>::read(&mut bytes.as_slice())?;
+        if params.k() != manifest.k {
+            return Err(KeyStoreError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("params file has k={}, manifest says k={}", params.k(), manifest.k),
+            )));
+        }
+        Ok(params)
+    }
+
+    // Load keys for num_arms, refusing any whose manifest doesn't match this build
+    pub fn load(&self, num_arms: u64) -> Result<CircuitKeys, KeyStoreError> {
+        let manifest = self.current_manifest(num_arms)?;
+        let params = self.load_params(&manifest)?;
+        let bytes = self.read_checked(num_arms, "pk", &manifest.files.pk)?;
+        let pk = ProvingKey::<EqAffine>::read::<_, SimpleSpiralsCircuit<Fp>>(&mut bytes.as_slice(), KEY_FORMAT)?;
+
+        Ok(CircuitKeys {
+            num_arms,
+            k: manifest.k,
+            params,
+            pk,
+        })
+    }
+
+    // Verifying key only, for verifier-side deployments that never prove
+    pub fn load_verifying_key(&self, num_arms: u64) -> Result<VerifyingKey<EqAffine>, KeyStoreError> {
+        let manifest = self.current_manifest(num_arms)?;
+        self.read_verifying_key(&manifest)
+    }
+
+    // Params and verifying key: everything verification needs, without the (much larger) pk
+    pub fn load_verifier(&self, num_arms: u64) -> Result<VerifierKeys, KeyStoreError> {
+        let manifest = self.current_manifest(num_arms)?;
+        Ok(VerifierKeys {
+            num_arms,
+            params: self.load_params(&manifest)?,
+            vk: self.read_verifying_key(&manifest)?,
+        })
+    }
+
+    fn read_verifying_key(&self, manifest: &KeyManifest) -> Result<VerifyingKey<EqAffine>, KeyStoreError> {
+        let bytes = self.read_checked(manifest.num_arms, "vk", &manifest.files.vk)?;
+        let vk = VerifyingKey::<EqAffine>::read::<_, SimpleSpiralsCircuit<Fp>>(&mut bytes.as_slice(), KEY_FORMAT)?;
+        Ok(vk)
+    }
+
+    // Write bytes to the key file and return their digest for the manifest
+    fn write_file(&self, num_arms: u64, extension: &str, bytes: &[u8]) -> io::Result<String> {
+        fs::write(self.path(num_arms, extension), bytes)?;
+        Ok(blake2b_hex(bytes))
+    }
+
+    // The manifest goes last, so an interrupted save leaves keys that won't be loaded
+    pub fn save(&self, keys: &CircuitKeys) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let _ = fs::remove_file(self.manifest_path(keys.num_arms));
+
+        let mut params = Vec::new();
+        keys.params.write(&mut params)?;
+        let mut vk = Vec::new();
+        keys.pk.get_vk().write(&mut vk, KEY_FORMAT)?;
+        let mut pk = Vec::new();
+        keys.pk.write(&mut pk, KEY_FORMAT)?;
+
+        let mut manifest = KeyManifest::current(keys.num_arms);
+        manifest.files = KeyFileDigests {
+            params: self.write_file(keys.num_arms, "params", &params)?,
+            vk: self.write_file(keys.num_arms, "vk", &vk)?,
+            pk: self.write_file(keys.num_arms, "pk", &pk)?,
+        };
+        let mut file = BufWriter::new(File::create(self.manifest_path(keys.num_arms))?);
+        serde_json::to_writer_pretty(&mut file, &manifest).map_err(io::Error::from)?;
+        file.flush()
+    }
+}
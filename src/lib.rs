@@ -1,11 +1,33 @@
 pub mod circuits {
+    pub mod range_check;
     pub mod simple_spirals;
 }
 
+pub mod api;
+pub mod backgrounds;
+pub mod config;
+pub mod contract;
+pub mod evm_sim;
+pub mod gas;
+pub mod generation;
+pub mod keystore;
+pub mod onchain_render;
+pub mod prices;
+pub mod procedural;
+pub mod prover;
+pub mod raster;
+pub mod render;
+pub mod server;
+pub mod svg;
+
 #[cfg(test)]
 mod tests {
     use super::circuits::simple_spirals::{
         SimpleSpiralsCircuit, 
+        calculate_public_inputs,
+        circuit_k
+    };
+    use super::generation::{
         calculate_configuration_mapping, 
         generate_spiral_particles,
         generate_spiral_triangles,
@@ -52,7 +74,8 @@ mod tests {
             triangle_metadata: vec![],
         };
 
-        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        let public_inputs = calculate_public_inputs::<Fp>(seed_u64, &[], &[]);
+        let prover = MockProver::run(12, &circuit, vec![public_inputs]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
         
         println!("✅ OPTIMIZED galaxy configuration verified!");
@@ -109,6 +132,9 @@ mod tests {
             ))
             .collect();
 
+        // Particles are only accepted together with the triangles drawn around them
+        let (triangles, triangle_metadata) = generate_spiral_triangles(spiral_type, num_arms, canvas_size);
+
         let circuit = SimpleSpiralsCircuit::<Fp> {
             seed: Value::known(Fp::from(seed_u64)),
             variant_id: Value::known(Fp::from(variant_u64)),
@@ -126,11 +152,27 @@ mod tests {
             particle_metadata,
             triangles_per_arm: Value::known(Fp::from(23u64)),
             total_triangles: Value::known(Fp::from(num_arms * 23)),
-            triangle_vertices: vec![],
-            triangle_metadata: vec![],
+            triangle_vertices: triangles.iter()
+                .map(|(x1, y1, x2, y2, x3, y3)| (
+                    Value::known(Fp::from(*x1)),
+                    Value::known(Fp::from(*y1)),
+                    Value::known(Fp::from(*x2)),
+                    Value::known(Fp::from(*y2)),
+                    Value::known(Fp::from(*x3)),
+                    Value::known(Fp::from(*y3))
+                ))
+                .collect(),
+            triangle_metadata: triangle_metadata.iter()
+                .map(|(arm_idx, triangle_idx, triangle_type)| (
+                    Value::known(Fp::from(*arm_idx)),
+                    Value::known(Fp::from(*triangle_idx)),
+                    Value::known(Fp::from(*triangle_type))
+                ))
+                .collect(),
         };
 
-        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        let public_inputs = calculate_public_inputs::<Fp>(seed_u64, &triangles, &triangle_metadata);
+        let prover = MockProver::run(circuit_k(num_arms), &circuit, vec![public_inputs]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
         
         println!("✅ OPTIMIZED particle generation verified!");
@@ -148,7 +190,7 @@ mod tests {
             calculate_configuration_mapping(variant_u64);
         
         // Generate particles and optimized micro-triangles
-        let (positions, _metadata) = generate_spiral_particles(spiral_type, num_arms, canvas_size);
+        let (positions, metadata) = generate_spiral_particles(spiral_type, num_arms, canvas_size);
         let (triangle_vertices, triangle_metadata) = generate_spiral_triangles(spiral_type, num_arms, canvas_size);
         
         println!("🔺 OPTIMIZED Micro-Triangle Generation Test:");
@@ -193,7 +235,14 @@ mod tests {
             .map(|(x, y)| (Value::known(Fp::from(*x)), Value::known(Fp::from(*y))))
             .collect();
             
-        let particle_metadata: Vec<(Value<Fp>, Value<Fp>, Value<Fp>)> = vec![];
+        // Triangles are constrained against the particle they are drawn around
+        let particle_metadata: Vec<(Value<Fp>, Value<Fp>, Value<Fp>)> = metadata.iter()
+            .map(|(arm_idx, particle_idx, angle_idx)| (
+                Value::known(Fp::from(*arm_idx)),
+                Value::known(Fp::from(*particle_idx)),
+                Value::known(Fp::from(*angle_idx))
+            ))
+            .collect();
         
         let triangle_vertices_circuit: Vec<(Value<Fp>, Value<Fp>, Value<Fp>, Value<Fp>, Value<Fp>, Value<Fp>)> = 
             triangle_vertices.iter()
@@ -237,7 +286,8 @@ mod tests {
             triangle_metadata: triangle_metadata_circuit,
         };
 
-        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        let public_inputs = calculate_public_inputs::<Fp>(seed_u64, &triangle_vertices, &triangle_metadata);
+        let prover = MockProver::run(circuit_k(num_arms), &circuit, vec![public_inputs]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
         
         println!("✅ OPTIMIZED micro-triangle generation verified!");
@@ -250,12 +300,11 @@ mod tests {
         println!("🎨 Testing Background Type Generation:");
         
         let test_seeds = vec![12345u64, 67890u64, 111u64, 999u64, 5555u64];
-        let background_names = ["Deep Space", "Nebula", "Starfield", "Cosmic", "Void"];
         
         for seed in test_seeds {
             let bg_type = generate_background_type(seed);
-            println!("  Seed {}: Background {} ({})", seed, bg_type, background_names[bg_type as usize]);
-            assert!(bg_type < 5, "Background type should be 0-4");
+            println!("  Seed {}: Background {}", seed, bg_type);
+            assert!(bg_type < 21, "Background type should be 0-20");
         }
         
         // Test deterministic behavior
@@ -329,36 +378,18 @@ mod tests {
             let variant = seed % 41;
             let quotient = seed / 41;
             
-            let (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder) = 
-                calculate_configuration_mapping(variant);
-            
+            let (spiral_type, num_arms, _, _, _) = calculate_configuration_mapping(variant);
             let background_type = generate_background_type(seed);
 
             println!("Testing OPTIMIZED Seed {} → variant {} (quotient: {})", seed, variant, quotient);
             println!("  Config: spiral_type={}, num_arms={}, background={}", spiral_type, num_arms, background_type);
 
-            let circuit = SimpleSpiralsCircuit::<Fp> {
-                seed: Value::known(Fp::from(seed)),
-                variant_id: Value::known(Fp::from(variant)),
-                quotient: Value::known(Fp::from(quotient)),
-                spiral_type: Value::known(Fp::from(spiral_type)),
-                num_arms: Value::known(Fp::from(num_arms)),
-                background_type: Value::known(Fp::from(background_type)),
-                spiral_quotient: Value::known(Fp::from(spiral_quotient)),
-                arms_quotient: Value::known(Fp::from(arms_quotient)),
-                arms_remainder: Value::known(Fp::from(arms_remainder)),
-                particles_per_arm: Value::known(Fp::from(23u64)), // OPTIMIZED
-                total_particles: Value::known(Fp::from(num_arms * 23)),
-                canvas_size: Value::known(Fp::from(420u64)), // Optimized canvas
-                particle_positions: vec![],
-                particle_metadata: vec![],
-                triangles_per_arm: Value::known(Fp::from(23u64)), // OPTIMIZED
-                total_triangles: Value::known(Fp::from(num_arms * 23)),
-                triangle_vertices: vec![],
-                triangle_metadata: vec![],
-            };
-
-            let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+            // Full galaxy: every particle and triangle witnessed
+            let circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed, 420);
+            let (triangles, triangle_metadata) = generate_spiral_triangles(spiral_type, num_arms, 420);
+
+            let public_inputs = calculate_public_inputs::<Fp>(seed, &triangles, &triangle_metadata);
+            let prover = MockProver::run(circuit_k(num_arms), &circuit, vec![public_inputs]).unwrap();
             assert_eq!(prover.verify(), Ok(()));
             
             println!("✅ OPTIMIZED Galaxy Verified: {} = {} * 41 + {}", seed, quotient, variant);
@@ -367,54 +398,27 @@ mod tests {
 
     #[test]
     fn test_optimized_complexity_limits() {
-        // Test circuit capacity with optimized complexity
-        let seed_u64 = 12345u64;
+        // Largest galaxy: seed 15 → variant 15 → 8 arms, 184 particles and triangles
+        let seed_u64 = 15u64;
         let variant_u64 = seed_u64 % 41;
         let quotient_u64 = seed_u64 / 41;
         
         let (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder) = 
             calculate_configuration_mapping(variant_u64);
+        assert_eq!(num_arms, 8);
         
+        let k = circuit_k(num_arms);
         println!("🔬 Testing OPTIMIZED Complexity Limits:");
         println!("  Expected total triangles: {}", num_arms * 23);
-        println!("  Circuit complexity: OPTIMIZED (reduced from 69 to 23)");
+        println!("  Circuit size: k = {} ({} rows)", k, 1u64 << k);
         
-        // Generate optimized data
         let (positions, metadata) = generate_spiral_particles(spiral_type, num_arms, 420);
         let (triangles, tri_metadata) = generate_spiral_triangles(spiral_type, num_arms, 420);
         
         println!("  Generated {} positions", positions.len());
         println!("  Generated {} triangles", triangles.len());
         
-        // Test with substantial but optimized data
-        let limited_positions: Vec<(Value<Fp>, Value<Fp>)> = positions.iter()
-            .take(30) // Reduced test size for optimized circuit
-            .map(|(x, y)| (Value::known(Fp::from(*x)), Value::known(Fp::from(*y))))
-            .collect();
-            
-        let limited_metadata: Vec<(Value<Fp>, Value<Fp>, Value<Fp>)> = metadata.iter()
-            .take(30)
-            .map(|(arm_idx, particle_idx, angle_idx)| (
-                Value::known(Fp::from(*arm_idx)),
-                Value::known(Fp::from(*particle_idx)), 
-                Value::known(Fp::from(*angle_idx))
-            ))
-            .collect();
-            
-        let limited_triangles: Vec<(Value<Fp>, Value<Fp>, Value<Fp>, Value<Fp>, Value<Fp>, Value<Fp>)> = 
-            triangles.iter()
-            .take(30)
-            .map(|(x1, y1, x2, y2, x3, y3)| (
-                Value::known(Fp::from(*x1)),
-                Value::known(Fp::from(*y1)),
-                Value::known(Fp::from(*x2)),
-                Value::known(Fp::from(*y2)),
-                Value::known(Fp::from(*x3)),
-                Value::known(Fp::from(*y3))
-            ))
-            .collect();
-
-        let circuit = SimpleSpiralsCircuit::<Fp> {
+        let build_circuit = |take: usize| SimpleSpiralsCircuit::<Fp> {
             seed: Value::known(Fp::from(seed_u64)),
             variant_id: Value::known(Fp::from(variant_u64)),
             quotient: Value::known(Fp::from(quotient_u64)),
@@ -427,19 +431,978 @@ mod tests {
             particles_per_arm: Value::known(Fp::from(23u64)),
             total_particles: Value::known(Fp::from(num_arms * 23)),
             canvas_size: Value::known(Fp::from(420u64)),
-            particle_positions: limited_positions,
-            particle_metadata: limited_metadata,
+            particle_positions: positions.iter()
+                .take(take)
+                .map(|(x, y)| (Value::known(Fp::from(*x)), Value::known(Fp::from(*y))))
+                .collect(),
+            particle_metadata: metadata.iter()
+                .take(take)
+                .map(|(arm_idx, particle_idx, angle_idx)| (
+                    Value::known(Fp::from(*arm_idx)),
+                    Value::known(Fp::from(*particle_idx)), 
+                    Value::known(Fp::from(*angle_idx))
+                ))
+                .collect(),
             triangles_per_arm: Value::known(Fp::from(23u64)),
             total_triangles: Value::known(Fp::from(num_arms * 23)),
-            triangle_vertices: limited_triangles,
-            triangle_metadata: vec![],
+            triangle_vertices: triangles.iter()
+                .take(take)
+                .map(|(x1, y1, x2, y2, x3, y3)| (
+                    Value::known(Fp::from(*x1)),
+                    Value::known(Fp::from(*y1)),
+                    Value::known(Fp::from(*x2)),
+                    Value::known(Fp::from(*y2)),
+                    Value::known(Fp::from(*x3)),
+                    Value::known(Fp::from(*y3))
+                ))
+                .collect(),
+            triangle_metadata: tri_metadata.iter()
+                .take(take)
+                .map(|(arm_idx, triangle_idx, triangle_type)| (
+                    Value::known(Fp::from(*arm_idx)),
+                    Value::known(Fp::from(*triangle_idx)),
+                    Value::known(Fp::from(*triangle_type))
+                ))
+                .collect(),
         };
 
-        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        // Every particle and triangle is assigned
+        let public_inputs = calculate_public_inputs::<Fp>(seed_u64, &triangles, &tri_metadata);
+        let prover = MockProver::run(k, &build_circuit(triangles.len()), vec![public_inputs]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
+
+        // A truncated galaxy fails synthesis instead of being silently cut off
+        let truncated_inputs = calculate_public_inputs::<Fp>(seed_u64, &triangles[..30], &tri_metadata[..30]);
+        assert!(MockProver::run(k, &build_circuit(30), vec![truncated_inputs]).is_err());
         
         println!("✅ OPTIMIZED complexity test passed!");
-        println!("✅ Circuit ready for optimized galaxy generation!");
-        println!("✅ Estimated gas savings: ~10.4M gas vs previous version!");
+        println!("✅ All {} triangles covered by the circuit!", triangles.len());
+    }
+
+    #[test]
+    fn test_real_proof_roundtrip() {
+        use super::prover::{
+            create_spiral_proof, generate_keys, generate_keys_for_arms, seed_k, setup_params,
+            spiral_public_inputs, verify_spiral_proof,
+        };
+
+        let seed_u64 = 12345u64;
+        let circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        let public_inputs = spiral_public_inputs(seed_u64, 420);
+
+        let params = setup_params(seed_k(seed_u64));
+        let pk = generate_keys(&params, &circuit).unwrap();
+
+        // Keys from the seed-free keygen shape are interchangeable with the seed's own
+        let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed_u64 % 41);
+        let shape_pk = generate_keys_for_arms(&params, num_arms).unwrap();
+        assert_eq!(shape_pk.get_vk().transcript_repr(), pk.get_vk().transcript_repr());
+        let proof = create_spiral_proof(&params, &pk, circuit, &public_inputs).unwrap();
+
+        println!("🔐 Real IPA proof for seed {}: {} bytes", seed_u64, proof.len());
+        assert!(verify_spiral_proof(&params, pk.get_vk(), &proof, &public_inputs).is_ok());
+
+        // A corrupted transcript must not verify
+        let mut tampered = proof.clone();
+        tampered[0] ^= 1;
+        assert!(verify_spiral_proof(&params, pk.get_vk(), &tampered, &public_inputs).is_err());
+
+        // Nor may the proof be replayed for a different seed
+        let other_inputs = spiral_public_inputs(seed_u64 + 1, 420);
+        assert!(verify_spiral_proof(&params, pk.get_vk(), &proof, &other_inputs).is_err());
+
+        println!("✅ Proof verified, tampered proof and wrong public inputs rejected!");
+    }
+
+    #[test]
+    fn test_key_cache_reuses_keys() {
+        use super::api::prove_response;
+        use super::prover::{prove_with_keys, KeyCache};
+
+        // Same variant (seed % 41), so same arm count and same keys. Concurrent requests
+        // for one shape wait on its lock and share a single keygen.
+        let cache = KeyCache::new();
+        let ((keys, first_cached), (again, cached)) = std::thread::scope(|scope| {
+            let first = scope.spawn(|| cache.for_seed(12345).unwrap());
+            let second = scope.spawn(|| cache.for_seed(12345 + 41).unwrap());
+            (first.join().unwrap(), second.join().unwrap())
+        });
+        assert!(first_cached != cached, "exactly one request generates the keys");
+        assert!(std::sync::Arc::ptr_eq(&keys, &again));
+        // Keys made for proving also serve verification
+        assert_eq!(cache.verifier(keys.num_arms).unwrap().vk.transcript_repr(), keys.pk.get_vk().transcript_repr());
+
+        let proof = prove_with_keys(&again, 12345 + 41, 420).unwrap();
+        let galaxy = super::generation::generate_galaxy(12345 + 41, 420);
+        let response = prove_response(&proof, &galaxy, 0, cached);
+        assert_eq!(response.num_arms, keys.num_arms);
+        assert_eq!(response.proof_size_bytes * 2, response.proof.len());
+        assert_eq!(response.public_inputs[0].name, "seed");
+        assert_eq!(response.public_inputs[0].value, format!("0x{:064x}", 12345 + 41));
+    }
+
+    #[test]
+    fn test_contract_triangle_packing_and_hash() {
+        use super::api::spiral_response;
+        use super::contract::{
+            keccak256, pack_triangle, pack_triangles, packed_triangles_hash, stored_triangles_hash,
+            triangle_storage_word, DEFAULT_TRIANGLE_OPACITY, PACKED_TRIANGLE_BYTES,
+        };
+        use super::generation::generate_galaxy;
+
+        // Standard keccak256 (not NIST SHA3-256) test vector
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+
+        let response = spiral_response(&generate_galaxy(12345, 420));
+        let triangle = &response.triangles[30];
+        let record = pack_triangle(triangle, DEFAULT_TRIANGLE_OPACITY);
+        let [(x1, y1), (x2, y2), (x3, y3)] = triangle.packed_vertices;
+        assert_eq!(
+            record,
+            [
+                x1, y1, x2, y2, x3, y3,
+                triangle.arm_index as u8,
+                triangle.triangle_index as u8,
+                triangle.triangle_type as u8,
+                80,
+            ]
+        );
+
+        // packTriangleData shifts x1 to bit 248 down to opacity at bit 176
+        let word = triangle_storage_word(&record);
+        assert_eq!(&word[..PACKED_TRIANGLE_BYTES], &record);
+        assert!(word[PACKED_TRIANGLE_BYTES..].iter().all(|&b| b == 0));
+
+        let packed = pack_triangles(&response.triangles, DEFAULT_TRIANGLE_OPACITY);
+        assert_eq!(packed.len(), response.triangles.len() * PACKED_TRIANGLE_BYTES);
+        assert_eq!(&packed[30 * PACKED_TRIANGLE_BYTES..31 * PACKED_TRIANGLE_BYTES], &record);
+
+        let calldata_hash = packed_triangles_hash(&response.triangles, DEFAULT_TRIANGLE_OPACITY);
+        assert_eq!(calldata_hash, keccak256(&packed));
+        assert_ne!(calldata_hash, stored_triangles_hash(&response.triangles, DEFAULT_TRIANGLE_OPACITY));
+        assert_ne!(calldata_hash, packed_triangles_hash(&response.triangles, 70));
+    }
+
+    #[test]
+    fn test_packed_bytes_unpack_roundtrip() {
+        use super::api::spiral_response;
+        use super::contract::{unpack_triangles, DEFAULT_TRIANGLE_OPACITY};
+        use super::generation::generate_galaxy;
+
+        for seed in [0u64, 7, 15, 12345, u64::MAX] {
+            let response = spiral_response(&generate_galaxy(seed, 420));
+            let packed = hex::decode(response.packed_bytes.strip_prefix("0x").unwrap()).unwrap();
+            assert_eq!(packed.len(), response.triangles.len() * 10);
+
+            let unpacked = unpack_triangles(&packed);
+            assert_eq!(unpacked.len(), response.triangles.len());
+            for (decoded, triangle) in unpacked.iter().zip(&response.triangles) {
+                assert_eq!(decoded.packed_vertices, triangle.packed_vertices);
+                assert_eq!(decoded.arm_index as u64, triangle.arm_index);
+                assert_eq!(decoded.triangle_index as u64, triangle.triangle_index);
+                assert_eq!(decoded.triangle_type as u64, triangle.triangle_type);
+                assert_eq!(decoded.opacity, DEFAULT_TRIANGLE_OPACITY);
+                assert!(decoded.is_valid(), "seed {} triangle would be rejected on-chain: {:?}", seed, decoded);
+            }
+        }
+    }
+
+    #[test]
+    fn test_onchain_svg_port() {
+        use super::api::spiral_response;
+        use super::contract::pack_triangles;
+        use super::generation::generate_galaxy;
+        use super::onchain_render::{diff_svg, render_onchain_svg, scale_coordinate, SvgDiffLine};
+        use super::render::generate_complete_svg;
+
+        assert_eq!(scale_coordinate(0), 0);
+        assert_eq!(scale_coordinate(128), 210); // 128 * 420 / 255 = 210.8, truncated
+        assert_eq!(scale_coordinate(255), 420);
+
+        // Seed 7: background (7 / 7) % 21 = 1 (Nebula)
+        let galaxy = generate_galaxy(7, 420);
+        let response = spiral_response(&galaxy);
+        let packed = pack_triangles(&response.triangles, 80);
+        let svg = render_onchain_svg(7, galaxy.spiral_type, galaxy.num_arms, &packed).unwrap();
+
+        assert!(svg.starts_with("<svg width=\"420\" height=\"420\" xmlns=\"http://www.w3.org/2000/svg\"><defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#2d1b4e\"/>"));
+        assert_eq!(svg.matches("<polygon").count(), response.triangles.len().min(50));
+        let [(x1, y1), _, _] = response.triangles[0].packed_vertices;
+        assert!(svg.contains(&format!("<polygon points=\"{},{} ", scale_coordinate(x1), scale_coordinate(y1))));
+        assert!(svg.ends_with(&format!(
+            "| Nebula | Arms: {} | ZK Verified</text></svg>",
+            galaxy.num_arms
+        )));
+
+        // _generateTitle only names 5 backgrounds: seed 12345 has background 20 and reverts
+        let other = generate_galaxy(12345, 420);
+        let other_packed = pack_triangles(&spiral_response(&other).triangles, 80);
+        assert!(render_onchain_svg(12345, other.spiral_type, other.num_arms, &other_packed).is_err());
+
+        // The diff keeps shared elements and flags the rest on the right side
+        assert!(diff_svg(&svg, &svg).iter().all(|line| matches!(line, SvgDiffLine::Same(_))));
+        let diff = diff_svg(&generate_complete_svg(&galaxy, true), &svg);
+        assert!(diff.iter().any(|line| matches!(line, SvgDiffLine::Same(_))));
+        assert!(diff.iter().any(|line| matches!(line, SvgDiffLine::OnchainOnly(_))));
+    }
+
+    #[test]
+    fn test_all_background_types_render() {
+        use super::backgrounds::BACKGROUNDS;
+        use super::generation::generate_background_type;
+        use super::onchain_render::render_onchain_svg;
+        use super::render::{background_svg, BACKGROUND_NAMES};
+        use std::collections::HashSet;
+
+        assert_eq!(BACKGROUND_NAMES[10], "Pulsar");
+        assert_eq!(BACKGROUND_NAMES[20], "Quantum Foam");
+
+        let mut fragments = HashSet::new();
+        for background_type in 0..BACKGROUNDS.len() as u64 {
+            // (seed / 7) % 21: seed 7 * t has background t
+            let seed = 7 * background_type;
+            assert_eq!(generate_background_type(seed), background_type);
+            let fragment = background_svg(background_type, seed, 420);
+            assert_eq!(fragment, background_svg(background_type, seed, 420), "background {} not deterministic", background_type);
+            assert!(fragments.insert(fragment), "background {} duplicates another", background_type);
+        }
+        assert_eq!(fragments.len(), 21);
+
+        // Seeded overlays differ between galaxies sharing a background (Supernova: seeds 84 and 231)
+        assert_eq!(generate_background_type(231), 12);
+        assert_ne!(background_svg(12, 84, 420), background_svg(12, 231, 420));
+
+        // The types the contract already draws keep its exact markup
+        // (apart from the per-galaxy gradient ID)
+        let onchain = render_onchain_svg(0, 0, 3, &[]).unwrap();
+        assert!(onchain.contains(&background_svg(0, 0, 420).replace("zkbg-0-420-bg", "bg")));
+        assert!(background_svg(99, 7, 420).contains("fill=\"#0a0a0a\""));
+        println!("✅ All 21 background types render distinctly!");
+    }
+
+    #[test]
+    fn test_backgrounds_scale_with_canvas() {
+        use super::backgrounds::BACKGROUNDS;
+        use super::generation::generate_galaxy;
+        use super::render::{background_svg, generate_complete_svg};
+
+        // Every number in an attribute like name="12" (paths: every number in d)
+        fn attribute_numbers(svg: &str, name: &str) -> Vec<i64> {
+            svg.split(&format!(" {}=\"", name))
+                .skip(1)
+                .flat_map(|rest| {
+                    let value = &rest[..rest.find('"').unwrap()];
+                    value.split(|c: char| !(c.is_ascii_digit() || c == '-'))
+                        .filter(|n| !n.is_empty())
+                        .map(|n| n.parse::<i64>().unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        }
+
+        for canvas_size in [128u64, 420, 1024, 2048] {
+            for background_type in 0..BACKGROUNDS.len() as u64 {
+                let fragment = background_svg(background_type, 7 * background_type + 3, canvas_size);
+                let base = format!("<rect width=\"{0}\" height=\"{0}\"", canvas_size);
+                assert!(fragment.contains(&base), "background {} doesn't fill {}px", background_type, canvas_size);
+
+                for name in ["cx", "cy", "x1", "y1", "x2", "y2", "d"] {
+                    for value in attribute_numbers(&fragment, name) {
+                        assert!(
+                            (0..=canvas_size as i64).contains(&value),
+                            "background {} at {}px: {}={} out of bounds", background_type, canvas_size, name, value
+                        );
+                    }
+                }
+                for radius in attribute_numbers(&fragment, "r") {
+                    assert!((1..=canvas_size as i64 / 2).contains(&radius));
+                }
+            }
+        }
+
+        // Starfield stars keep their layout and move with the canvas
+        let (small, large) = (background_svg(2, 14, 420), background_svg(2, 14, 1024));
+        let (small_x, large_x) = (attribute_numbers(&small, "cx"), attribute_numbers(&large, "cx"));
+        assert_eq!(small_x.len(), large_x.len());
+        assert!(small_x.iter().zip(&large_x).all(|(s, l)| *l == s * 1024 / 420));
+
+        // The full export no longer leaves a 420px background in the corner
+        let svg = generate_complete_svg(&generate_galaxy(12345, 1024), true);
+        assert!(!svg.contains("width=\"420\""));
+        assert!(svg.contains("<rect width=\"1024\" height=\"1024\""));
+        println!("✅ Backgrounds stay in bounds at 128-2048px!");
+    }
+
+    #[test]
+    fn test_procedural_layers_seeded() {
+        use super::backgrounds::SeedRng;
+        use super::generation::generate_background_type;
+        use super::procedural::{dust_lane, nebula_clouds, star_scatter, STAR_COLORS};
+        use super::render::background_svg;
+
+        // Same seed, same sky; seeds 14 and 161 are both Starfield but differ
+        assert_eq!(generate_background_type(14), 2);
+        assert_eq!(generate_background_type(161), 2);
+        assert_eq!(background_svg(2, 14, 420), background_svg(2, 14, 420));
+        assert_ne!(background_svg(2, 14, 420), background_svg(2, 161, 420));
+        assert_ne!(background_svg(1, 7, 420), background_svg(1, 154, 420));
+
+        let stars = star_scatter(&mut SeedRng::new(14, 0), 120, 420);
+        assert_eq!(stars.len(), 120);
+        assert!(stars.iter().all(|s| STAR_COLORS.contains(&s.color) && (1..=2).contains(&s.radius)));
+        assert!(stars.iter().any(|s| s.opacity < 100), "expected faint stars");
+        assert!(stars.iter().any(|s| s.radius == 2), "expected a few bright stars");
+
+        // Every puff and dust grain stays fully on the frame
+        let clouds = nebula_clouds(&mut SeedRng::new(7, 0), 3, 8, &["#7b3fa0", "#c2409e"], 12, 420);
+        let lane = dust_lane(&mut SeedRng::new(7, 1), 14, 30, "#1a0d05", 35, 420);
+        assert_eq!((clouds.len(), lane.len()), (24, 14));
+        for dot in clouds.iter().chain(&lane) {
+            assert!(dot.x >= dot.radius && dot.x + dot.radius <= 420, "{:?}", dot);
+            assert!(dot.y >= dot.radius && dot.y + dot.radius <= 420, "{:?}", dot);
+        }
+        // Puffs shrink along each cloud's walk
+        assert!(clouds[0].radius > clouds[7].radius);
+        println!("✅ Procedural starfield, nebula and dust are seeded and reproducible!");
+    }
+
+    #[test]
+    fn test_svg_builder_escaping_and_ids() {
+        use super::generation::generate_galaxy;
+        use super::render::generate_complete_svg;
+        use super::svg::{circle, escape, galaxy_namespace, gradient, group, url, Element, GradientKind, SvgDocument};
+
+        assert_eq!(escape(r#"<a href="x">Tom & 'Jerry'</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;");
+
+        let mut doc = SvgDocument::new(100, 50, galaxy_namespace(42, 100));
+        let id = doc.id("glow");
+        assert_eq!(id, "zkbg-42-100-glow");
+        assert!(doc.define(gradient(GradientKind::Radial, &id, &[(0, "#fff"), (100, "#000")])));
+        doc.push(
+            group()
+                .attr("class", "a\"b")
+                .child(circle(5, 5, 2).attr("fill", url(&id)))
+                .child(Element::new("text").text("1 < 2 & 3")),
+        );
+        let expected = "<svg width=\"100\" height=\"50\" xmlns=\"http://www.w3.org/2000/svg\">\
+             <defs><radialGradient id=\"zkbg-42-100-glow\"><stop offset=\"0%\" stop-color=\"#fff\"/>\
+             <stop offset=\"100%\" stop-color=\"#000\"/></radialGradient></defs>\
+             <g class=\"a&quot;b\"><circle cx=\"5\" cy=\"5\" r=\"2\" fill=\"url(#zkbg-42-100-glow)\"/>\
+             <text>1 &lt; 2 &amp; 3</text></g></svg>";
+        assert_eq!(doc.render(), expected);
+
+        // Defining the same ID twice keeps the first definition instead of emitting both
+        assert!(!doc.define(Element::new("linearGradient").attr("id", "zkbg-42-100-glow")));
+        assert_eq!(doc.render(), expected);
+
+        // Two galaxies inlined in one page don't share any gradient ID
+        let ids = |svg: &str| -> Vec<String> {
+            svg.split(" id=\"").skip(1).map(|rest| rest[..rest.find('"').unwrap()].to_string()).collect()
+        };
+        let (a, b) = (generate_complete_svg(&generate_galaxy(7, 420), true), generate_complete_svg(&generate_galaxy(154, 420), true));
+        assert_eq!(ids(&a), vec!["zkbg-7-420-bg"]);
+        assert_eq!(ids(&b), vec!["zkbg-154-420-bg"]);
+        assert!(a.contains("fill=\"url(#zkbg-7-420-bg)\""));
+        // Nor does one galaxy shown at two sizes
+        assert_eq!(ids(&generate_complete_svg(&generate_galaxy(7, 840), true)), vec!["zkbg-7-840-bg"]);
+        println!("✅ SVG builder escapes and namespaces IDs!");
+    }
+
+    #[test]
+    fn test_png_export_deterministic() {
+        use super::generation::generate_galaxy;
+        use super::raster::{render_galaxy_png, render_svg_png, MAX_PNG_CANVAS_SIZE};
+        use super::render::generate_complete_svg;
+        use resvg::tiny_skia::Pixmap;
+
+        // Void (background 4, seeds 28-34) is a flat #0a0a0a
+        let galaxy = generate_galaxy(28, 256);
+        let first = render_galaxy_png(&galaxy, true).unwrap();
+        let second = render_galaxy_png(&generate_galaxy(28, 256), true).unwrap();
+        assert_eq!(first, second, "PNG bytes differ between runs");
+        assert_eq!(&first[..8], b"\x89PNG\r\n\x1a\n");
+
+        let pixmap = Pixmap::decode_png(&first).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (256, 256));
+        let corner = pixmap.pixel(0, 0).unwrap();
+        assert_eq!((corner.red(), corner.green(), corner.blue(), corner.alpha()), (0x0a, 0x0a, 0x0a, 255));
+
+        // Triangles are drawn: some pixel differs from the flat background
+        assert!(pixmap.pixels().iter().any(|p| p.red() > 0x40 || p.green() > 0x40 || p.blue() > 0x40));
+
+        // The title caption is what the PNG omits (PNG_OMITTED_ELEMENTS): dropping it changes nothing
+        let svg = generate_complete_svg(&galaxy, true);
+        let (start, end) = (svg.find("<text").unwrap(), svg.find("</text>").unwrap() + "</text>".len());
+        let untitled = format!("{}{}", &svg[..start], &svg[end..]);
+        assert_eq!(render_svg_png(&untitled).unwrap(), first);
+
+        // Backgrounds with gradients, seeded layers and namespaced IDs all rasterize
+        for seed in [7u64, 14, 84, 126, 140] {
+            assert!(render_galaxy_png(&generate_galaxy(seed, 128), true).is_ok(), "seed {}", seed);
+        }
+        assert!(render_galaxy_png(&generate_galaxy(28, MAX_PNG_CANVAS_SIZE + 1), true).is_err());
+        assert!(render_svg_png("<not-svg").is_err());
+        println!("✅ PNG export is byte-identical across runs ({} bytes)", first.len());
+    }
+
+    #[test]
+    fn test_evm_simulation_gas() {
+        use super::evm_sim::EvmSimulator;
+
+        // Creation code returning runtime `PUSH1 1 PUSH1 0 SSTORE STOP`
+        let init_code = hex::decode("6006600c60003960066000f3600160005500").unwrap();
+        let mut sim = EvmSimulator::new();
+        let (address, _) = sim.deploy(init_code).unwrap();
+
+        // 21000 intrinsic + 2 * PUSH1 (3) + cold zero-to-nonzero SSTORE (22100)
+        let first = sim.call(address, vec![]).unwrap();
+        assert!(first.success);
+        assert_eq!(first.gas_used, 43106);
+        // Rewriting the same value: cold slot access (2100) + no-op write (100)
+        assert!(sim.call(address, vec![]).unwrap().gas_used < first.gas_used);
+    }
+
+    // The compiled contract isn't checked in, so this one has to be asked for explicitly
+    #[test]
+    #[ignore = "needs `npx hardhat compile` in blockchain/; run with `cargo test -- --ignored`"]
+    fn test_evm_simulation_zkbg_mint() {
+        use super::evm_sim::{load_artifact_bytecode, simulate_mint_gas, DEFAULT_ARTIFACT_PATH};
+        use super::generation::generate_galaxy;
+
+        let bytecode = load_artifact_bytecode(DEFAULT_ARTIFACT_PATH)
+            .unwrap_or_else(|e| panic!("zkBgNFT artifact missing: {:#}", e));
+        let galaxy = generate_galaxy(15, 420); // 8 arms, 184 triangles
+        let simulated = simulate_mint_gas(&bytecode, &galaxy, 50).unwrap();
+        assert_eq!(simulated.triangle_count, 184);
+        assert_eq!(simulated.add_triangle_batch_gas.len(), 4);
+        // Each batch stores 50 triangles' worth of fresh slots, far above the 21000 intrinsic cost
+        assert!(simulated.add_triangle_batch_gas.iter().all(|&gas| gas > 100_000));
+        assert_eq!(
+            simulated.total_mint_gas,
+            simulated.initialize_galaxy_gas + simulated.add_triangle_batch_gas.iter().sum::<u64>()
+        );
+        println!("⛽ Simulated mint gas for seed 15: {:?}", simulated);
+    }
+
+    #[tokio::test]
+    async fn test_price_sources_report_fallback() {
+        use super::evm_sim::SimulatedGas;
+        use super::gas::{calculate_gas_costs, estimate_optimized_gas, FALLBACK_ETH_PRICE_USD, FALLBACK_GAS_PRICE_GWEI};
+        use super::generation::generate_galaxy;
+        use super::prices::{MockPriceSource, PriceCache, PriceSourceOptions, PriceSources, StaticFileSource, UsdPrices};
+        use std::time::Duration;
+
+        let galaxy = generate_galaxy(12345, 420);
+
+        let mock = PriceCache::new(PriceSources {
+            gas: Box::new(MockPriceSource { gas_price_gwei: 10.0, ..MockPriceSource::default() }),
+            usd: Box::new(MockPriceSource { usd: UsdPrices { eth: 2000.0, btc: 40000.0 }, ..MockPriceSource::default() }),
+        }, Duration::ZERO);
+        let measured = SimulatedGas {
+            seed: galaxy.seed,
+            triangle_count: galaxy.triangles.len() as u64,
+            deploy_gas: 5_000_000,
+            initialize_galaxy_gas: 250_000,
+            add_triangle_batch_gas: vec![900_000, 400_000],
+            total_mint_gas: 1_550_000,
+        };
+        let response = calculate_gas_costs(&galaxy, &mock, Ok(measured)).await;
+        assert_eq!(response.gas_price_gwei, 10.0);
+        assert_eq!(response.eth_price_usd, 2000.0);
+        assert_eq!(response.gas_price_source.source, "mock");
+        assert!(!response.gas_price_source.fallback && !response.usd_price_source.fallback);
+        assert_eq!(response.estimated_gas_units, 1_550_000);
+        assert_eq!(response.breakdown.triangle_storage_gas, 1_300_000);
+        assert_eq!(response.gas_units_source.source, "evm-simulation");
+        assert!(!response.gas_units_source.fallback);
+
+        // Without a measurement the constant estimate is used and flagged like a price fallback
+        let response = calculate_gas_costs(&galaxy, &mock, Err("no artifact".to_string())).await;
+        assert_eq!(response.estimated_gas_units, estimate_optimized_gas(&galaxy).0.total_gas);
+        assert!(response.gas_units_source.fallback);
+        assert_eq!(response.gas_units_source.error.as_deref(), Some("no artifact"));
+
+        // A static file that isn't there falls back, and says so
+        let missing = PriceCache::new(PriceSources {
+            gas: Box::new(StaticFileSource::new("/nonexistent/zkbg_prices.json")),
+            usd: Box::new(StaticFileSource::new("/nonexistent/zkbg_prices.json")),
+        }, Duration::from_secs(60));
+        let response = calculate_gas_costs(&galaxy, &missing, Err("no artifact".to_string())).await;
+        assert_eq!(response.gas_price_gwei, FALLBACK_GAS_PRICE_GWEI);
+        assert_eq!(response.eth_price_usd, FALLBACK_ETH_PRICE_USD);
+        assert!(response.gas_price_source.fallback);
+        assert!(response.usd_price_source.error.as_deref().unwrap().contains("zkbg_prices.json"));
+
+        // CoinGecko has no gas price; unknown names are rejected at startup
+        let options = PriceSourceOptions::default();
+        let (_, report) = PriceSources::by_name("coingecko", "mock", &options).unwrap().gas_price_gwei().await;
+        assert!(report.fallback);
+        assert!(PriceSources::by_name("carrier-pigeon", "mock", &options).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_price_cache_ttl_and_stale_fallback() {
+        use super::prices::{MockPriceSource, PriceCache, PriceSource, PriceSources};
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        // Counts upstream calls and can be switched off to simulate an outage
+        struct FlakyGas {
+            calls: Arc<AtomicUsize>,
+            down: Arc<AtomicBool>,
+        }
+
+        #[async_trait::async_trait]
+        impl PriceSource for FlakyGas {
+            fn name(&self) -> &'static str {
+                "flaky"
+            }
+
+            async fn gas_price_gwei(&self) -> anyhow::Result<f64> {
+                let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+                if self.down.load(Ordering::SeqCst) {
+                    anyhow::bail!("upstream down");
+                }
+                Ok(call as f64)
+            }
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let down = Arc::new(AtomicBool::new(false));
+        let sources = |calls: &Arc<AtomicUsize>| PriceSources {
+            gas: Box::new(FlakyGas { calls: calls.clone(), down: down.clone() }),
+            usd: Box::new(MockPriceSource::default()),
+        };
+
+        // Within the TTL the upstream is asked once and the age is reported
+        let cache = PriceCache::new(sources(&calls), Duration::from_secs(60));
+        let (first, report) = cache.gas_price_gwei().await;
+        assert_eq!((first, report.age_ms), (1.0, 0));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let (second, report) = cache.gas_price_gwei().await;
+        assert_eq!(second, 1.0);
+        assert!(report.age_ms >= 20 && !report.fallback);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A forced refresh during an outage keeps serving the last good value, with the error
+        down.store(true, Ordering::SeqCst);
+        cache.refresh().await;
+        let (stale, report) = cache.gas_price_gwei().await;
+        assert_eq!(stale, 1.0);
+        assert!(!report.fallback);
+        assert_eq!(report.error.as_deref(), Some("upstream down"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // TTL 0 goes upstream every time; with nothing cached an outage falls back
+        let uncached_calls = Arc::new(AtomicUsize::new(0));
+        let uncached = PriceCache::new(sources(&uncached_calls), Duration::ZERO);
+        let (_, report) = uncached.gas_price_gwei().await;
+        uncached.gas_price_gwei().await;
+        assert!(report.fallback);
+        assert_eq!(uncached_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_price_cache_backs_off_failing_source() {
+        use super::prices::{MockPriceSource, PriceCache, PriceSources};
+        use std::sync::atomic::Ordering;
+        use std::time::Duration;
+
+        let down = MockPriceSource { error: Some("upstream down".to_string()), ..MockPriceSource::default() };
+        let calls = down.calls.clone();
+        let cache = PriceCache::new(
+            PriceSources { gas: Box::new(down), usd: Box::new(MockPriceSource::default()) },
+            Duration::from_millis(200),
+        );
+
+        // Nothing cached and the source is down: one upstream call per TTL, fallback every time
+        for _ in 0..5 {
+            let (_, report) = cache.gas_price_gwei().await;
+            assert!(report.fallback);
+            assert_eq!(report.error.as_deref(), Some("upstream down"));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Once the TTL has passed since the failed attempt, it is tried again (once)
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        cache.gas_price_gwei().await;
+        cache.gas_price_gwei().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_config_layering_and_validation() {
+        use super::config::{parse_cli, ServerConfig};
+        use std::collections::HashMap;
+
+        let args = |list: &[&str]| parse_cli(list.iter().map(|s| s.to_string())).unwrap();
+        let no_env = |_: &str| None;
+
+        // Defaults: no key configured, so gas prices come from a local node
+        let config = ServerConfig::load_with_env(&args(&[]), no_env).unwrap();
+        assert_eq!(config.bind_addr().unwrap().port(), 3030);
+        assert_eq!(config.gas_source(), "json-rpc");
+        assert_eq!(config.allowed_origins, vec!["*"]);
+        assert!(config.price_source_options().etherscan_api_key.is_none());
+
+        // File < environment < flags
+        let dir = std::env::temp_dir().join(format!("zkbg_config_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("zkbg.toml");
+        std::fs::write(&file, r#"
+            bind = "0.0.0.0:8080"
+            default_canvas_size = 600
+            allowed_origins = ["https://zkbg.example"]
+
+            [prices]
+            usd_source = "static"
+
+            [api_keys]
+            etherscan = "file-key"
+        "#).unwrap();
+        let env: HashMap<&str, &str> = [
+            ("ZKBG_BIND", "127.0.0.1:9000"),
+            ("ZKBG_DEFAULT_CANVAS_SIZE", "800"),
+            ("ZKBG_ARTIFACT_PATH", "out/zkBgNFT.json"),
+        ].into();
+        let env_lookup = |name: &str| env.get(name).map(|v| v.to_string());
+
+        let cli = args(&[
+            "--config", file.to_str().unwrap(),
+            "--bind=127.0.0.1:9100",
+            "--allowed-origin", "http://localhost:3000",
+            "--allowed-origin", "http://127.0.0.1:3000",
+        ]);
+        let config = ServerConfig::load_with_env(&cli, env_lookup).unwrap();
+        assert_eq!(config.bind, "127.0.0.1:9100");
+        assert_eq!(config.default_canvas_size, 800);
+        assert_eq!(config.artifact_path, std::path::PathBuf::from("out/zkBgNFT.json"));
+        assert_eq!(config.prices.usd_source, "static");
+        assert_eq!(config.gas_source(), "etherscan");
+        assert_eq!(config.allowed_origins, vec!["http://localhost:3000", "http://127.0.0.1:3000"]);
+        assert!(!format!("{:?}", config).contains("file-key"));
+
+        // Everything is checked before the server starts
+        let rejected = [
+            vec!["--gas-source", "etherscan"],
+            vec!["--price-source", "json-rpc"],
+            vec!["--default-canvas-size", "64"],
+            vec!["--allowed-origin", "localhost:3000"],
+            vec!["--bind", "localhost"],
+            vec!["--static-dir", "/nonexistent/zkbg_static"],
+            vec!["--colour", "blue"],
+            vec!["--price-cache-ttl", "0"],
+        ];
+        for flags in rejected {
+            assert!(ServerConfig::load_with_env(&args(&flags), no_env).is_err(), "{:?} accepted", flags);
+        }
+        std::fs::write(&file, "bind_address = \"0.0.0.0:1\"").unwrap();
+        assert!(ServerConfig::load_with_env(&args(&["--config", file.to_str().unwrap()]), no_env).is_err());
+        assert!(parse_cli(vec!["--bind".to_string()]).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_request_canvas_size_bounds() {
+        use super::api::canvas_size_or;
+        use super::config::{MAX_CANVAS_SIZE, MIN_CANVAS_SIZE};
+        use super::generation::generate_galaxy;
+
+        // Missing or 0 falls back to the configured default
+        assert_eq!(canvas_size_or(None, 600), Ok(600));
+        assert_eq!(canvas_size_or(Some(0), 600), Ok(600));
+
+        // Lower bound: smallest provable canvas
+        assert_eq!(canvas_size_or(Some(MIN_CANVAS_SIZE), 420), Ok(MIN_CANVAS_SIZE));
+        let error = canvas_size_or(Some(MIN_CANVAS_SIZE - 1), 420).unwrap_err();
+        assert!(error.contains("127") && error.contains("128..=65535"), "{}", error);
+
+        // Upper bound: generation must not overflow at the limit itself
+        assert_eq!(canvas_size_or(Some(MAX_CANVAS_SIZE), 420), Ok(MAX_CANVAS_SIZE));
+        generate_galaxy(12345, MAX_CANVAS_SIZE);
+        assert!(canvas_size_or(Some(MAX_CANVAS_SIZE + 1), 420).is_err());
+        assert!(canvas_size_or(Some(u64::MAX), 420).is_err());
+    }
+
+    #[test]
+    fn test_contract_calldata_encoding() {
+        use super::api::{calldata_response, spiral_response};
+        use super::contract::{
+            add_triangle_batch_calldata, function_selector, mint_spiral_calldata, pack_triangles,
+            parse_address, DEFAULT_TRIANGLE_OPACITY, MINT_SPIRAL_SIGNATURE,
+        };
+        use super::generation::generate_galaxy;
+
+        // ERC-20 transfer selector as a known-answer check
+        assert_eq!(function_selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+
+        let word = |data: &[u8], i: usize| data[4 + i * 32..4 + (i + 1) * 32].to_vec();
+        let uint = |value: u64| {
+            let mut w = vec![0u8; 32];
+            w[24..].copy_from_slice(&value.to_be_bytes());
+            w
+        };
+
+        // addTriangleBatch(uint256 tokenId, bytes data, bool last): bytes in the tail at 0x60
+        let batch = add_triangle_batch_calldata(7, &[1u8; 25], true);
+        assert_eq!(word(&batch, 0), uint(7));
+        assert_eq!(word(&batch, 1), uint(0x60));
+        assert_eq!(word(&batch, 2), uint(1));
+        assert_eq!(word(&batch, 3), uint(25));
+        assert_eq!(batch.len(), 4 + 5 * 32); // 25 bytes padded to one word
+
+        // mintSpiral: address, 8-word config tuple, bytes offset, bytes32, string offset
+        let galaxy = generate_galaxy(15, 420);
+        let to = parse_address("0x00000000000000000000000000000000000000aa").unwrap();
+        let packed = pack_triangles(&spiral_response(&galaxy).triangles, DEFAULT_TRIANGLE_OPACITY);
+        let mint = mint_spiral_calldata(to, &galaxy, &packed, [0x11; 32], "ipfs://x");
+        assert_eq!(&mint[..4], &function_selector(MINT_SPIRAL_SIGNATURE));
+        assert_eq!(word(&mint, 0)[31], 0xaa);
+        assert_eq!(word(&mint, 1), uint(15));
+        assert_eq!(word(&mint, 4), uint(galaxy.num_arms));
+        assert_eq!(word(&mint, 9), uint(12 * 32));
+        assert_eq!(word(&mint, 10), vec![0x11; 32]);
+        let bytes_words = 1 + packed.len().div_ceil(32);
+        assert_eq!(word(&mint, 11), uint(((12 + bytes_words) * 32) as u64));
+        assert_eq!(word(&mint, 12), uint(packed.len() as u64));
+
+        // 8 arms = 184 triangles; batches of 50 => 50, 50, 50, 34 with only the last flagged
+        let response = calldata_response(&galaxy, to, Some(3), None, "", 50);
+        let batches = response.add_triangle_batches.unwrap();
+        assert_eq!(response.expected_triangles, 184);
+        assert_eq!(batches.len(), 4);
+        let last_flags: Vec<bool> = batches.iter()
+            .map(|call| hex::decode(&call.data[2..]).unwrap()[4 + 3 * 32 - 1] == 1)
+            .collect();
+        assert_eq!(last_flags, vec![false, false, false, true]);
+        assert!(calldata_response(&galaxy, to, None, None, "", 50).add_triangle_batches.is_none());
+    }
+
+    #[test]
+    fn test_verify_claim_mismatches() {
+        use super::api::{field_to_hex, hex_to_field, public_input_mismatches, VerifyRequest};
+        use super::prover::spiral_public_inputs;
+
+        let expected = spiral_public_inputs(12345, 420);
+        for value in &expected {
+            assert_eq!(hex_to_field(&field_to_hex(value)), Some(*value));
+        }
+        assert_eq!(hex_to_field("0x07"), Some(Fp::from(7)));
+        assert_eq!(hex_to_field("not hex"), None);
+
+        let mut request = VerifyRequest {
+            proof: String::new(),
+            seed: 12345,
+            canvas_size: None,
+            variant_id: None,
+            spiral_type: None,
+            num_arms: None,
+            background_type: None,
+            public_inputs: Some(expected.iter().map(field_to_hex).collect()),
+        };
+        assert!(public_input_mismatches(&request, &expected).unwrap().is_empty());
+
+        // A wrong arm count and a wrong commitment are both named
+        let (_, num_arms, _, _, _) = calculate_configuration_mapping(12345 % 41);
+        request.num_arms = Some(num_arms + 1);
+        request.public_inputs.as_mut().unwrap()[5] = field_to_hex(&(expected[5] + Fp::from(1)));
+        let mismatches = public_input_mismatches(&request, &expected).unwrap();
+        let names: Vec<&str> = mismatches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["num_arms", "triangle_commitment"]);
+
+        request.public_inputs = Some(vec!["0x01".to_string()]);
+        assert!(public_input_mismatches(&request, &expected).is_err());
+    }
+
+    #[test]
+    fn test_persisted_keys_roundtrip_and_staleness() {
+        use super::keystore::{KeyManifest, KeyStore, KeyStoreError};
+        use super::prover::KeyCache;
+
+        let dir = std::env::temp_dir().join(format!("zkbg_keys_{}", std::process::id()));
+        let store = KeyStore::new(&dir);
+        assert!(matches!(store.load(3), Err(KeyStoreError::Missing)));
+
+        let (generated, cached) = KeyCache::with_store(store.clone()).get_or_generate(3).unwrap();
+        assert!(!cached);
+        assert!(store.read_manifest(3).unwrap().same_circuit(&KeyManifest::current(3)));
+
+        // A fresh cache (as after a restart) loads the same keys instead of regenerating
+        let (loaded, cached) = KeyCache::with_store(store.clone()).get_or_generate(3).unwrap();
+        assert!(cached);
+        let vk = generated.pk.get_vk().transcript_repr();
+        assert_eq!(loaded.pk.get_vk().transcript_repr(), vk);
+        assert_eq!(store.load_verifying_key(3).unwrap().transcript_repr(), vk);
+
+        // Verification reads the persisted vk. Without key files it generates only a vk, the
+        // same one full keygen produces, and never writes a pk
+        let verifier_only = KeyCache::with_store(store.clone());
+        assert_eq!(verifier_only.verifier(3).unwrap().vk.transcript_repr(), vk);
+        assert_eq!(KeyCache::new().verifier(3).unwrap().vk.transcript_repr(), vk);
+        assert_eq!(verifier_only.verifier(5).unwrap().num_arms, 5);
+        assert!(matches!(store.load(5), Err(KeyStoreError::Missing)));
+
+        // A key file that no longer matches its manifest digest is refused, not decoded
+        let vk_path = store.dir().join("spirals_3arms.vk");
+        let vk_bytes = std::fs::read(&vk_path).unwrap();
+        let mut corrupted = vk_bytes.clone();
+        corrupted[0] ^= 1;
+        std::fs::write(&vk_path, &corrupted).unwrap();
+        assert!(matches!(store.load_verifying_key(3), Err(KeyStoreError::Corrupt(_))));
+        assert!(matches!(store.load_verifier(3), Err(KeyStoreError::Corrupt(_))));
+        std::fs::write(&vk_path, &vk_bytes).unwrap();
+        assert!(store.load_verifier(3).is_ok());
+
+        // Keys from a circuit with different gates are refused
+        let mut stale = KeyManifest::current(3);
+        stale.constraint_system_hash = "00".repeat(32);
+        std::fs::write(store.manifest_path(3), serde_json::to_string(&stale).unwrap()).unwrap();
+        assert!(matches!(store.load(3), Err(KeyStoreError::Stale { .. })));
+        assert!(matches!(store.load_verifying_key(3), Err(KeyStoreError::Stale { .. })));
+        // Stale files aren't trusted for verification either; the vk is regenerated
+        assert_eq!(KeyCache::with_store(store.clone()).verifier(3).unwrap().vk.transcript_repr(), vk);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_geometry_rejected() {
+        use super::circuits::simple_spirals::INSTANCE_TRIANGLE_COMMITMENT;
+        use super::prover::spiral_public_inputs;
+
+        let seed_u64 = 12345u64;
+        let public_inputs = spiral_public_inputs(seed_u64, 420);
+        let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed_u64 % 41);
+        let k = circuit_k(num_arms);
+
+        // Shift one particle by a pixel: it no longer matches calculate_spiral_point
+        let mut circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        circuit.particle_positions[5].0 = circuit.particle_positions[5].0 + Value::known(Fp::from(1));
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Claim a wrong angle index for a particle (trig lookup must fail)
+        let mut circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        circuit.particle_metadata[7].2 = circuit.particle_metadata[7].2 + Value::known(Fp::from(1));
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Grow a triangle away from its particle
+        let mut circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        circuit.triangle_vertices[3].4 = circuit.triangle_vertices[3].4 + Value::known(Fp::from(1));
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Drop every triangle: the commitment would be 0 and bind no geometry
+        let mut circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        circuit.triangle_vertices.clear();
+        circuit.triangle_metadata.clear();
+        let mut empty_inputs = public_inputs;
+        empty_inputs[INSTANCE_TRIANGLE_COMMITMENT] = Fp::from(0);
+        assert!(MockProver::run(k, &circuit, vec![empty_inputs]).is_err());
+
+        println!("✅ Tampered particle, angle and triangle witnesses rejected!");
+    }
+
+    #[test]
+    fn test_out_of_range_decomposition_rejected() {
+        // 12345 = 301 * 41 + 4, but also = 300 * 41 + 45 if variant_id were unbounded
+        let seed_u64 = 12345u64;
+        let forged_variant = seed_u64 % 41 + 41;
+        let (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder) =
+            calculate_configuration_mapping(forged_variant);
+
+        let mut circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        circuit.variant_id = Value::known(Fp::from(forged_variant));
+        circuit.quotient = Value::known(Fp::from(seed_u64 / 41 - 1));
+        circuit.spiral_type = Value::known(Fp::from(spiral_type));
+        circuit.num_arms = Value::known(Fp::from(num_arms));
+        circuit.spiral_quotient = Value::known(Fp::from(spiral_quotient));
+        circuit.arms_quotient = Value::known(Fp::from(arms_quotient));
+        circuit.arms_remainder = Value::known(Fp::from(arms_remainder));
+        circuit.total_particles = Value::known(Fp::from(num_arms * 23));
+        circuit.total_triangles = Value::known(Fp::from(num_arms * 23));
+        circuit.particle_positions.clear();
+        circuit.particle_metadata.clear();
+        circuit.triangle_vertices.clear();
+        circuit.triangle_metadata.clear();
+
+        let public_inputs = vec![
+            Fp::from(seed_u64),
+            Fp::from(forged_variant),
+            Fp::from(spiral_type),
+            Fp::from(num_arms),
+            Fp::from(generate_background_type(seed_u64)),
+            Fp::from(0),
+        ];
+        let prover = MockProver::run(12, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+
+        println!("✅ variant_id >= 41 rejected by the range check!");
+    }
+
+    #[test]
+    fn test_background_type_constrained() {
+        use super::prover::spiral_public_inputs;
+        use super::circuits::simple_spirals::INSTANCE_BACKGROUND_TYPE;
+
+        let seed_u64 = 12345u64;
+        let background_type = generate_background_type(seed_u64);
+        let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed_u64 % 41);
+
+        // Claiming a different background, even consistently in the public inputs, must fail
+        let forged = (background_type + 1) % 21;
+        let mut circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed_u64, 420);
+        circuit.background_type = Value::known(Fp::from(forged));
+        let mut public_inputs = spiral_public_inputs(seed_u64, 420);
+        public_inputs[INSTANCE_BACKGROUND_TYPE] = Fp::from(forged);
+
+        let prover = MockProver::run(circuit_k(num_arms), &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+
+        println!("✅ Background {} is the only accepted value for seed {}!", background_type, seed_u64);
+    }
+
+    #[test]
+    fn test_api_geometry_matches_circuit_witnesses() {
+        use super::api::spiral_response;
+        use super::generation::generate_galaxy;
+        use super::prover::spiral_public_inputs;
+
+        // One seed per arm count, at a few canvas sizes
+        for seed in [0u64, 3, 6, 9, 12, 15, 12345] {
+            for canvas_size in [256u64, 420, 1024] {
+                let galaxy = generate_galaxy(seed, canvas_size);
+                let response = spiral_response(&galaxy);
+                let circuit = SimpleSpiralsCircuit::<Fp>::from_seed(seed, canvas_size);
+
+                assert_eq!(circuit.particle_positions.len(), response.particles.len());
+                for ((x, y), particle) in circuit.particle_positions.iter().zip(response.particles.iter()) {
+                    x.assert_if_known(|x| *x == Fp::from(particle.x));
+                    y.assert_if_known(|y| *y == Fp::from(particle.y));
+                }
+
+                assert_eq!(circuit.triangle_vertices.len(), response.triangles.len());
+                for ((x1, y1, x2, y2, x3, y3), triangle) in circuit.triangle_vertices.iter().zip(response.triangles.iter()) {
+                    let witnessed = [(x1, y1), (x2, y2), (x3, y3)];
+                    for ((wx, wy), (x, y)) in witnessed.into_iter().zip(triangle.vertices) {
+                        wx.assert_if_known(|wx| *wx == Fp::from(x));
+                        wy.assert_if_known(|wy| *wy == Fp::from(y));
+                    }
+                }
+
+                // ...and the circuit accepts exactly those coordinates
+                let public_inputs = spiral_public_inputs(seed, canvas_size);
+                let prover = MockProver::run(circuit_k(galaxy.num_arms), &circuit, vec![public_inputs]).unwrap();
+                assert_eq!(prover.verify(), Ok(()), "seed {} canvas {}", seed, canvas_size);
+            }
+        }
+
+        println!("✅ API geometry matches the circuit witnesses!");
+    }
+}
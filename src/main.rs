@@ -1,663 +1,56 @@
-use warp::Filter;
-use serde::{Deserialize, Serialize};
-use reqwest;
-use anyhow::Result;
-
-// Import optimized circuit modules
-mod circuits {
-    pub mod simple_spirals;
-}
-
-use circuits::simple_spirals::{
-    calculate_configuration_mapping, 
-    generate_background_type
-};
-
-#[derive(Serialize, Deserialize)]
-struct SpiralRequest {
-    seed: u64,
-    canvas_size: u64,
-}
-
-#[derive(Serialize)]
-struct SpiralResponse {
-    seed: u64,
-    variant: u64,
-    spiral_type: u64,
-    num_arms: u64,
-    background_type: u64, // Background type (0-20)
-    particles: Vec<ParticleData>,
-    triangles: Vec<TriangleData>,
-    config: ConfigData,
-    // Galaxy-specific metadata
-    galaxy_stats: GalaxyStats,
-}
-
-#[derive(Serialize)]
-struct ParticleData {
-    x: u64,
-    y: u64,
-    arm_index: u64,
-    particle_index: u64,
-}
-
-#[derive(Serialize)]
-struct TriangleData {
-    vertices: [(u64, u64); 3],
-    arm_index: u64,
-    triangle_index: u64,
-    triangle_type: u64, // 0=spiral particle, 1=star, 2=core, 3=dust
-    size: u64,          // Triangle size for micro-triangle rendering
-    // Add scaled coordinates for uint8 packing
-    packed_vertices: [(u8, u8); 3], // Scaled to 0-255 for smart contract
-}
-
-#[derive(Serialize)]
-struct ConfigData {
-    spiral_type: u64,
-    num_arms: u64,
-    background_type: u64, // Background type (0-20)
-    spiral_quotient: u64,
-    arms_quotient: u64,
-    arms_remainder: u64,
-}
-
-// Optimized galaxy-specific statistics
-#[derive(Serialize)]
-struct GalaxyStats {
-    particles_per_arm: u64,
-    total_particles: u64,
-    micro_triangles: u64,
-    galaxy_type: String,
-    background_type: String,
-    density_factor: f64,      // Relative to original (now 23/69)
-    gas_savings_estimate: u64, // Estimated gas savings
-}
-
-// SVG Export request
-#[derive(Serialize, Deserialize)]
-struct SVGExportRequest {
-    seed: u64,
-    canvas_size: Option<u64>,
-    include_background: Option<bool>,
-}
-
-#[derive(Serialize)]
-struct SVGExportResponse {
-    svg_content: String,
-    spiral_data: SpiralResponse,
-    export_info: SVGExportInfo,
-}
-
-#[derive(Serialize)]
-struct SVGExportInfo {
-    canvas_size: u64,
-    triangle_count: u64,
-    background_included: bool,
-    file_size_bytes: usize,
-    export_timestamp: u64,
-}
-
-// Gas estimation structures (updated for optimized data)
-#[derive(Serialize, Deserialize)]
-struct GasRequest {
-    seed: Option<u64>,
-    canvas_size: Option<u64>,
-}
-
-#[derive(Serialize)]
-struct GasResponse {
-    gas_price_gwei: f64,
-    estimated_gas_units: u64,
-    total_gas_cost_eth: f64,
-    total_gas_cost_usd: f64,
-    total_gas_cost_btc: f64,
-    eth_price_usd: f64,
-    btc_price_usd: f64,
-    breakdown: GasBreakdown,
-    optimization_savings: OptimizationSavings,
-    timestamp: u64,
-}
-
-#[derive(Serialize)]
-struct GasBreakdown {
-    base_mint_gas: u64,
-    zk_proof_gas: u64,
-    metadata_gas: u64,
-    triangle_storage_gas: u64,
-    total_gas: u64,
-}
-
-#[derive(Serialize)]
-struct OptimizationSavings {
-    triangle_reduction_savings: u64,
-    data_packing_savings: u64,
-    total_savings: u64,
-    original_estimate: u64,
-    optimized_estimate: u64,
-    savings_percentage: f64,
-}
-
-// External API response structures
-#[derive(Deserialize)]
-struct EtherscanGasResponse {
-    result: EtherscanGasResult,
-}
-
-#[derive(Deserialize)]
-struct EtherscanGasResult {
-    #[serde(rename = "SafeGasPrice")]
-    safe_gas_price: String,
-    #[serde(rename = "ProposeGasPrice")]
-    propose_gas_price: String,
-    #[serde(rename = "FastGasPrice")]
-    fast_gas_price: String,
-}
-
-#[derive(Deserialize)]
-struct CoinGeckoResponse {
-    ethereum: CurrencyPrice,
-    bitcoin: CurrencyPrice,
-}
-
-#[derive(Deserialize)]
-struct CurrencyPrice {
-    usd: f64,
-}
-
-// Helper function to scale coordinates to uint8 range (0-255)
-fn scale_to_uint8(coord: u64, canvas_size: u64) -> u8 {
-    ((coord * 255) / canvas_size).min(255) as u8
-}
-
-// Helper function to scale uint8 back to canvas coordinates
-fn scale_from_uint8(coord: u8, canvas_size: u64) -> u64 {
-    ((coord as u64 * canvas_size) / 255).min(canvas_size)
-}
-
-// OPTIMIZED: Galaxy spiral point calculation with enhanced distribution for 23 particles
-fn calculate_optimized_spiral_point(
-    arm_index: u64,
-    particle_index: u64, 
-    total_arms: u64,
-    spiral_type: u64,
-    canvas_size: u64,
-) -> (u64, u64, u64) {
-    const TRIG_TABLE_SIZE: u64 = 32;
-    const SCALE_FACTOR: i64 = 10000;
-    
-    const SIN_TABLE: [i64; 32] = [
-        0, 1951, 3827, 5556, 7071, 8315, 9239, 9808,
-        10000, 9808, 9239, 8315, 7071, 5556, 3827, 1951,
-        0, -1951, -3827, -5556, -7071, -8315, -9239, -9808,
-        -10000, -9808, -9239, -8315, -7071, -5556, -3827, -1951,
-    ];
-    
-    const COS_TABLE: [i64; 32] = [
-        10000, 9808, 9239, 8315, 7071, 5556, 3827, 1951,
-        0, -1951, -3827, -5556, -7071, -8315, -9239, -9808,
-        -10000, -9808, -9239, -8315, -7071, -5556, -3827, -1951,
-        0, 1951, 3827, 5556, 7071, 8315, 9239, 9808,
-    ];
-    
-    let particles_per_arm = 23u64; // OPTIMIZED: Reduced from 69
-    let max_radius = (canvas_size * 40) / 100; // Galaxy spread
-    
-    let base_angle_index = (arm_index * TRIG_TABLE_SIZE) / total_arms;
-    let t = (particle_index * 1000) / particles_per_arm;
-    
-    // OPTIMIZED GALAXY SPIRAL PATTERNS for 23 particles
-    let progression = match spiral_type {
-        0 => (t * 22) / 1000, // Tight galaxy - adjusted for 23 particles
-        1 => (t * 4) / 1000,  // Loose galaxy - adjusted for 23 particles
-        _ => (t * 12) / 1000, // Classic galaxy - adjusted for 23 particles
-    };
-    
-    let angle_index = (base_angle_index + progression) % TRIG_TABLE_SIZE;
-    let radius = (t * max_radius) / 1000;
-    
-    let sin_val = SIN_TABLE[angle_index as usize];
-    let cos_val = COS_TABLE[angle_index as usize];
-    
-    let center = canvas_size / 2;
-    
-    // Enhanced positioning for optimized galaxy effect - FIXED trigonometry
-    let x = ((center as i64) + ((radius as i64 * cos_val) / SCALE_FACTOR)).max(0) as u64;
-    let y = ((center as i64) + ((radius as i64 * sin_val) / SCALE_FACTOR)).max(0) as u64;
-    
-    (x, y, angle_index)
-}
-
-// OPTIMIZED: Generate micro-triangles for 23 particles per arm
-fn generate_optimized_triangles(positions: &[(u64, u64)], num_arms: u64, canvas_size: u64) -> Vec<TriangleData> {
-    let particles_per_arm = 23u64; // OPTIMIZED: Reduced from 69
-    let mut triangles = Vec::new();
-    
-    for arm_index in 0..num_arms {
-        for particle_index in 0..particles_per_arm {
-            let base_particle_idx = (arm_index * particles_per_arm + particle_index) as usize;
-            
-            if base_particle_idx < positions.len() {
-                let center = positions[base_particle_idx];
-                
-                // OPTIMIZED: Micro-triangle sizing for 23 particles
-                let base_size = 6u64;
-                let size_reduction = (particle_index * 3) / particles_per_arm; // Faster reduction for fewer particles
-                let triangle_size = (base_size - size_reduction).max(2); // Minimum 2 pixels
-                
-                // Create micro-triangle vertices around the center point
-                let half_size = triangle_size / 2;
-                let vertex1 = (center.0, center.1.saturating_sub(half_size));           // Top
-                let vertex2 = (center.0.saturating_sub(half_size), center.1 + half_size); // Bottom left
-                let vertex3 = (center.0 + half_size, center.1 + half_size);            // Bottom right
-                
-                // Create packed coordinates for smart contract (uint8)
-                let packed_vertex1 = (scale_to_uint8(vertex1.0, canvas_size), scale_to_uint8(vertex1.1, canvas_size));
-                let packed_vertex2 = (scale_to_uint8(vertex2.0, canvas_size), scale_to_uint8(vertex2.1, canvas_size));
-                let packed_vertex3 = (scale_to_uint8(vertex3.0, canvas_size), scale_to_uint8(vertex3.1, canvas_size));
-                
-                triangles.push(TriangleData {
-                    vertices: [vertex1, vertex2, vertex3],
-                    arm_index,
-                    triangle_index: particle_index,
-                    triangle_type: 0, // Type 0 = spiral particle
-                    size: triangle_size,
-                    packed_vertices: [packed_vertex1, packed_vertex2, packed_vertex3],
-                });
-            }
+// Thin HTTP front-end over the zkbg library (see zkbg::server for the routes)
+#[tokio::main]
+async fn main() {
+    // defaults < zkbg.toml (or --config) < ZKBG_* environment < flags, validated before anything starts
+    let config = match zkbg::config::parse_cli(std::env::args().skip(1)) {
+        Ok(cli) if cli.help => {
+            println!("{}", zkbg::config::USAGE);
+            return;
         }
-    }
-    
-    triangles
-}
-
-// Generate complete SVG with background and optimized triangles
-fn generate_complete_svg(spiral_data: &SpiralResponse, canvas_size: u64, include_background: bool) -> String {
-    // UPDATED: 21 background types (0-20)
-    let background_names = [
-        "Deep Space", "Nebula", "Starfield", "Cosmic", "Void",           // 0-4
-        "Aurora", "Galaxy Core", "Solar Wind", "Dark Matter", "Quasar",  // 5-9
-        "Pulsar", "Black Hole", "Supernova", "Comet Trail", "Asteroid",  // 10-14
-        "Plasma Storm", "Ion Cloud", "Magnetosphere", "Cosmic Web", "Gamma Burst", // 15-19
-        "Quantum Foam"                                                    // 20
-    ];
-    
-    let background_name = if spiral_data.background_type < 21 {
-        background_names[spiral_data.background_type as usize]
-    } else {
-        "Unknown"
+        Ok(cli) => zkbg::config::ServerConfig::load(&cli),
+        Err(e) => Err(e),
     };
-    
-    let mut svg = format!(
-        "<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">",
-        canvas_size, canvas_size
-    );
-    
-    // Add background if requested
-    if include_background {
-        let bg_svg = match spiral_data.background_type {
-            0 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#0a0a2e\"/><stop offset=\"100%\" stop-color=\"#1a1a3a\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>",
-            1 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#2d1b4e\"/><stop offset=\"50%\" stop-color=\"#4a2c5a\"/><stop offset=\"100%\" stop-color=\"#1a0f2e\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>",
-            2 => "<rect width=\"420\" height=\"420\" fill=\"#000000\"/><circle cx=\"50\" cy=\"50\" r=\"1\" fill=\"white\"/><circle cx=\"150\" cy=\"100\" r=\"1\" fill=\"white\"/><circle cx=\"300\" cy=\"80\" r=\"1\" fill=\"white\"/><circle cx=\"380\" cy=\"200\" r=\"1\" fill=\"white\"/><circle cx=\"100\" cy=\"300\" r=\"1\" fill=\"white\"/>",
-            3 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#2c1810\"/><stop offset=\"100%\" stop-color=\"#4a2f1a\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>",
-            4 => "<rect width=\"420\" height=\"420\" fill=\"#0a0a0a\"/>", // Void
-            5 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#001a33\"/><stop offset=\"100%\" stop-color=\"#003366\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>", // Aurora
-            6 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#ffcc00\"/><stop offset=\"100%\" stop-color=\"#ff6600\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>", // Galaxy Core
-            7 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#336699\"/><stop offset=\"100%\" stop-color=\"#003366\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>", // Solar Wind
-            8 => "<rect width=\"420\" height=\"420\" fill=\"#1a0d26\"/>", // Dark Matter
-            9 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#ff3366\"/><stop offset=\"100%\" stop-color=\"#990033\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>", // Quasar
-            // Add more backgrounds for 10-20...
-            _ => "<rect width=\"420\" height=\"420\" fill=\"#0a0a0a\"/>", // Default
-        };
-        svg.push_str(bg_svg);
-    } else {
-        svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"#0a0a0a\"/>", canvas_size, canvas_size));
-    }
-    
-    // Add triangles
-    let arm_colors = ["#ff6b6b", "#4ecdc4", "#45b7d1", "#96ceb4", "#feca57", "#ff9ff3", "#a8e6cf", "#ff8b94"];
-    
-    for triangle in &spiral_data.triangles {
-        let color = arm_colors[triangle.arm_index as usize % arm_colors.len()];
-        let vertices = triangle.vertices;
-        
-        svg.push_str(&format!(
-            "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" opacity=\"0.7\"/>",
-            vertices[0].0, vertices[0].1,
-            vertices[1].0, vertices[1].1,
-            vertices[2].0, vertices[2].1,
-            color
-        ));
-    }
-    
-    // Add title
-    let spiral_types = ["Tight", "Loose", "Classic"];
-    let title = format!(
-        "Seed: {} | {} {} | {} | Arms: {} | ZK Verified",
-        spiral_data.seed,
-        spiral_types[spiral_data.spiral_type as usize],
-        "Galaxy",
-        background_name,
-        spiral_data.num_arms
-    );
-    
-    svg.push_str(&format!(
-        "<text x=\"10\" y=\"{}\" fill=\"white\" font-family=\"monospace\" font-size=\"10\">{}</text>",
-        canvas_size - 10,
-        title
-    ));
-    
-    svg.push_str("</svg>");
-    svg
-}
-
-async fn generate_spiral_data(seed: u64, canvas_size: u64) -> Result<SpiralResponse, warp::Rejection> {
-    // FIXED: Respect the requested canvas size (instead of always using 420)
-    let canvas_size = if canvas_size > 0 { canvas_size } else { 420u64 };
-    
-    // Use optimized ZK circuit logic
-    let variant = seed % 41;
-    let (spiral_type, num_arms, spiral_quotient, arms_quotient, arms_remainder) = 
-        calculate_configuration_mapping(variant);
-    
-    // Generate background type (0-20)
-    let background_type = generate_background_type(seed);
-    
-    // Generate OPTIMIZED particles (23 per arm instead of 69)
-    let particles_per_arm = 23u64;
-    let mut positions = Vec::new();
-    let mut metadata = Vec::new();
-    
-    for arm_index in 0..num_arms {
-        for particle_index in 0..particles_per_arm {
-            let (x, y, angle_index) = calculate_optimized_spiral_point(
-                arm_index,
-                particle_index,
-                num_arms,
-                spiral_type,
-                canvas_size,
-            );
-            
-            positions.push((x, y));
-            metadata.push((arm_index, particle_index, angle_index));
+    let config = config.unwrap_or_else(|e| {
+        eprintln!("❌ Invalid configuration: {:#}", e);
+        std::process::exit(1);
+    });
+    let addr = config.bind_addr().expect("validated at load");
+
+    // Proving keys persist across restarts; stale ones are regenerated
+    let store = zkbg::keystore::KeyStore::new(config.keys_dir.clone());
+    let keys = std::sync::Arc::new(zkbg::prover::KeyCache::with_store(store));
+    let sources = match zkbg::prices::PriceSources::from_config(&config) {
+        Ok(sources) => sources,
+        Err(e) => {
+            eprintln!("❌ {:#}", e);
+            std::process::exit(1);
         }
-    }
-    
-    // Generate OPTIMIZED micro-triangles
-    let triangles = generate_optimized_triangles(&positions, num_arms, canvas_size);
-    
-    // Convert to response format
-    let particles: Vec<ParticleData> = positions.iter().zip(metadata.iter())
-        .map(|((x, y), (arm_index, particle_index, _angle_index))| ParticleData {
-            x: *x,
-            y: *y,
-            arm_index: *arm_index,
-            particle_index: *particle_index,
-        })
-        .collect();
-    
-    let config = ConfigData {
-        spiral_type,
-        num_arms,
-        background_type,
-        spiral_quotient,
-        arms_quotient,
-        arms_remainder,
     };
-    
-    // Background type names (21 total)
-    let background_names = [
-        "Deep Space", "Nebula", "Starfield", "Cosmic", "Void",           // 0-4
-        "Aurora", "Galaxy Core", "Solar Wind", "Dark Matter", "Quasar",  // 5-9
-        "Pulsar", "Black Hole", "Supernova", "Comet Trail", "Asteroid",  // 10-14
-        "Plasma Storm", "Ion Cloud", "Magnetosphere", "Cosmic Web", "Gamma Burst", // 15-19
-        "Quantum Foam"                                                    // 20
-    ];
-    
-    let background_name = if background_type < 21 {
-        background_names[background_type as usize].to_string()
-    } else {
-        "Unknown".to_string()
-    };
-    
-    let galaxy_type = match spiral_type {
-        0 => "Tight Galaxy".to_string(),
-        1 => "Loose Galaxy".to_string(),
-        _ => "Classic Galaxy".to_string(),
-    };
-    
-    // OPTIMIZED: Galaxy statistics with gas savings
-    let galaxy_stats = GalaxyStats {
-        particles_per_arm,
-        total_particles: num_arms * particles_per_arm,
-        micro_triangles: triangles.len() as u64,
-        galaxy_type,
-        background_type: background_name,
-        density_factor: particles_per_arm as f64 / 69.0, // Show reduction from original
-        gas_savings_estimate: 10_400_000, // Estimated 10.4M gas savings
-    };
-    
-    Ok(SpiralResponse {
-        seed,
-        variant,
-        spiral_type,
-        num_arms,
-        background_type,
-        particles,
-        triangles,
-        config,
-        galaxy_stats,
-    })
-}
-
-// Gas estimation functions with OPTIMIZED complexity
-async fn fetch_eth_gas_price() -> Result<f64> {
-    let url = "https://api.etherscan.io/api?module=gastracker&action=gasoracle&apikey=MF1UH981PQBWJXHNWNQW6AAX3A3ERVGYGH";
-    
-    let client = reqwest::Client::new();
-    let response = client.get(url).send().await?;
-    
-    if response.status().is_success() {
-        let gas_data: EtherscanGasResponse = response.json().await?;
-        // Use fast_gas_price instead of propose_gas_price
-        let gas_price = gas_data.result.fast_gas_price.parse::<f64>()?;
-        Ok(gas_price)
-    } else {
-        Ok(20.0) // Fallback
+    println!("💱 Price sources: gas={}, usd={}", sources.gas.name(), sources.usd.name());
+    let prices = std::sync::Arc::new(zkbg::prices::PriceCache::new(sources, config.price_cache_ttl()));
+    if let Some(interval) = config.price_refresh_interval() {
+        prices.clone().spawn_refresh(interval);
+        println!("🔄 Refreshing prices every {}s (cache TTL {}s)", interval.as_secs(), prices.ttl().as_secs());
     }
-}
-
-async fn fetch_crypto_prices() -> Result<(f64, f64)> {
-    let url = "https://api.coingecko.com/api/v3/simple/price?ids=ethereum,bitcoin&vs_currencies=usd";
-    
-    let client = reqwest::Client::new();
-    let response = client
-        .get(url)
-        .send()
-        .await?;
-    
-    if response.status().is_success() {
-        let price_data: CoinGeckoResponse = response.json().await?;
-        Ok((price_data.ethereum.usd, price_data.bitcoin.usd))
-    } else {
-        Ok((3000.0, 45000.0)) // Fallback
+    // Read the contract once; without it /api/gas/simulate is 503 and /api/gas falls back to estimates
+    let artifact = zkbg::evm_sim::ContractArtifact::load(config.artifact_path.clone());
+    match artifact.bytecode() {
+        Ok(bytecode) => println!("🧪 Contract artifact: {} ({} bytes)", artifact.path.display(), bytecode.len()),
+        Err(e) => eprintln!("⚠️  Gas simulation unavailable: {}", e),
     }
-}
-
-fn estimate_optimized_gas(spiral_data: &SpiralResponse) -> (GasBreakdown, OptimizationSavings) {
-    // OPTIMIZED gas costs
-    let base_mint_gas = 50000u64;
-    let zk_proof_gas = 100000u64; // Reduced complexity
-    
-    // OPTIMIZED: Calculate gas for 23 triangles instead of 69
-    let triangle_count = spiral_data.triangles.len() as u64;
-    let triangle_storage_gas = triangle_count * 5000; // Reduced per triangle due to packing
-    
-    let metadata_gas = 30000u64; // Reduced metadata complexity
-    
-    let total_optimized = base_mint_gas + zk_proof_gas + triangle_storage_gas + metadata_gas;
-    
-    let breakdown = GasBreakdown {
-        base_mint_gas,
-        zk_proof_gas,
-        metadata_gas,
-        triangle_storage_gas,
-        total_gas: total_optimized,
-    };
-    
-    // Calculate savings compared to original 69-triangle approach
-    let original_triangle_count = (spiral_data.num_arms * 69) as u64;
-    let original_storage_gas = original_triangle_count * 20000; // Original gas per triangle
-    let original_total = base_mint_gas + 120000 + original_storage_gas + 50000;
-    
-    let triangle_reduction_savings = original_storage_gas - triangle_storage_gas;
-    let data_packing_savings = 50000; // Estimated savings from uint8 packing
-    let total_savings = triangle_reduction_savings + data_packing_savings;
-    
-    let savings = OptimizationSavings {
-        triangle_reduction_savings,
-        data_packing_savings,
-        total_savings,
-        original_estimate: original_total,
-        optimized_estimate: total_optimized,
-        savings_percentage: (total_savings as f64 / original_total as f64) * 100.0,
-    };
-    
-    (breakdown, savings)
-}
-
-async fn calculate_gas_costs_for_spiral(spiral_data: &SpiralResponse) -> Result<GasResponse, warp::Rejection> {
-    let gas_price_gwei = match fetch_eth_gas_price().await {
-        Ok(price) => price,
-        Err(_) => 20.0,
-    };
-    
-    let (eth_price_usd, btc_price_usd) = match fetch_crypto_prices().await {
-        Ok(prices) => prices,
-        Err(_) => (3000.0, 45000.0),
-    };
-    
-    // OPTIMIZED gas estimates
-    let (breakdown, optimization_savings) = estimate_optimized_gas(spiral_data);
-    
-    let gas_price_eth = gas_price_gwei / 1_000_000_000.0;
-    let total_gas_cost_eth = (breakdown.total_gas as f64) * gas_price_eth;
-    let total_gas_cost_usd = total_gas_cost_eth * eth_price_usd;
-    let total_gas_cost_btc = total_gas_cost_usd / btc_price_usd;
-    
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    
-    Ok(GasResponse {
-        gas_price_gwei,
-        estimated_gas_units: breakdown.total_gas,
-        total_gas_cost_eth,
-        total_gas_cost_usd,
-        total_gas_cost_btc,
-        eth_price_usd,
-        btc_price_usd,
-        breakdown,
-        optimization_savings,
-        timestamp,
-    })
-}
-
-async fn handle_gas_estimation(request: GasRequest) -> Result<impl warp::Reply, warp::Rejection> {
-    let seed = request.seed.unwrap_or(12345u64);
-    let canvas_size = request.canvas_size.unwrap_or(420u64);
-    
-    let spiral_data = generate_spiral_data(seed, canvas_size).await?;
-    let response = calculate_gas_costs_for_spiral(&spiral_data).await?;
-    Ok(warp::reply::json(&response))
-}
-
-async fn handle_generate_spiral(request: SpiralRequest) -> Result<impl warp::Reply, warp::Rejection> {
-    let response = generate_spiral_data(request.seed, request.canvas_size).await?;
-    Ok(warp::reply::json(&response))
-}
-
-// Handle SVG export for gas estimation verification
-async fn handle_svg_export(request: SVGExportRequest) -> Result<impl warp::Reply, warp::Rejection> {
-    let canvas_size = request.canvas_size.unwrap_or(420);
-    let include_background = request.include_background.unwrap_or(true);
-    
-    // Generate spiral data
-    let spiral_data = generate_spiral_data(request.seed, canvas_size).await?;
-    
-    // Generate complete SVG
-    let svg_content = generate_complete_svg(&spiral_data, canvas_size, include_background);
-    
-    // Create export info
-    let export_info = SVGExportInfo {
-        canvas_size,
-        triangle_count: spiral_data.triangles.len() as u64,
-        background_included: include_background,
-        file_size_bytes: svg_content.len(),
-        export_timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-    };
-    
-    let response = SVGExportResponse {
-        svg_content,
-        spiral_data,
-        export_info,
-    };
-    
-    Ok(warp::reply::json(&response))
-}
-
-#[tokio::main]
-async fn main() {
-    // API route for generating OPTIMIZED galaxy spirals
-    let api_generate = warp::path("api")
-        .and(warp::path("generate"))
-        .and(warp::post())
-        .and(warp::body::json())
-        .and_then(handle_generate_spiral);
-    
-    // API route for OPTIMIZED gas estimation
-    let api_gas = warp::path("api")
-        .and(warp::path("gas"))
-        .and(warp::post())
-        .and(warp::body::json())
-        .and_then(handle_gas_estimation);
-    
-    // API route for SVG export (for verification during gas estimation)
-    let api_svg_export = warp::path("api")
-        .and(warp::path("export"))
-        .and(warp::path("svg"))
-        .and(warp::post())
-        .and(warp::body::json())
-        .and_then(handle_svg_export);
-    
-    // Serve static files
-    let static_files = warp::fs::dir("static");
-    
-    // Health check endpoint
-    let health = warp::path("health")
-        .map(|| "🌌 zkBg OPTIMIZED Galaxy Server Running!");
-    
-    let routes = api_generate
-        .or(api_gas)
-        .or(api_svg_export)
-        .or(static_files)
-        .or(health)
-        .with(
-            warp::cors()
-                .allow_any_origin()
-                .allow_headers(vec!["content-type", "authorization"])
-                .allow_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
-        );
-    
-    println!("🌌 zkBg OPTIMIZED Galaxy Server starting on http://localhost:3030");
-    println!("🎨 Enhanced Visualizer: http://localhost:3030/spiral_visualizer_hardhat.html");
-    println!("🔧 Optimized Galaxy API: POST http://localhost:3030/api/generate");
-    println!("⛽ Optimized Gas API: POST http://localhost:3030/api/gas");
-    println!("🖼️  SVG Export API: POST http://localhost:3030/api/export/svg");
+    println!("🌐 Allowed origins: {}", config.allowed_origins.join(", "));
+    let routes = zkbg::server::routes(&config, keys, prices, std::sync::Arc::new(artifact));
+    
+    println!("🌌 zkBg OPTIMIZED Galaxy Server starting on http://{addr}");
+    println!("🎨 Enhanced Visualizer: http://{addr}/spiral_visualizer_hardhat.html");
+    println!("🔧 Optimized Galaxy API: POST http://{addr}/api/generate");
+    println!("⛽ Optimized Gas API: POST http://{addr}/api/gas");
+    println!("🧪 Simulated Gas API: POST http://{addr}/api/gas/simulate");
+    println!("🖼️  SVG Export API: POST http://{addr}/api/export/svg");
+    println!("🏞️  PNG Export API: POST http://{addr}/api/export/png");
+    println!("📜 Calldata API: POST http://{addr}/api/calldata");
+    println!("🔐 Proof API: POST http://{addr}/api/prove");
+    println!("✅ Verify API: POST http://{addr}/api/verify");
     println!("\n🚀 Phase 1: OPTIMIZED Galaxy Micro-Triangles");
     println!("   • 23 particles per arm (reduced from 69)");
     println!("   • uint8 coordinate packing for gas efficiency");
@@ -667,6 +60,6 @@ async fn main() {
     println!("   • ZK-verified galaxy generation");
     
     warp::serve(routes)
-        .run(([127, 0, 0, 1], 3030))
+        .run(addr)
         .await;
-}
\ No newline at end of file
+}
@@ -0,0 +1,114 @@
+use crate::contract::{unpack_triangles, CONTRACT_CANVAS_SIZE};
+use crate::generation::generate_background_type;
+
+// _generateTriangles stops after this many triangles to bound gas
+pub const ONCHAIN_TRIANGLE_LIMIT: usize = 50;
+
+const ONCHAIN_ARM_COLORS: [&str; 6] = ["#ff6b6b", "#4ecdc4", "#45b7d1", "#96ceb4", "#feca57", "#ff9ff3"];
+const ONCHAIN_SPIRAL_TYPES: [&str; 3] = ["Tight", "Loose", "Classic"];
+// Only 5 names on-chain, although backgrounds go up to 20
+const ONCHAIN_BACKGROUND_NAMES: [&str; 5] = ["Deep Space", "Nebula", "Starfield", "Cosmic", "Void"];
+
+// scaleCoordinate: uint16((uint256(coord) * CANVAS_SIZE) / 255)
+pub fn scale_coordinate(coord: u8) -> u16 {
+    ((coord as u64 * CONTRACT_CANVAS_SIZE) / 255) as u16
+}
+
+// _generateBackground: only DEEP_SPACE, NEBULA and QUANTUM_FOAM have their own art
+fn onchain_background(background_type: u64) -> &'static str {
+    match background_type {
+        0 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#0a0a2e\"/><stop offset=\"100%\" stop-color=\"#1a1a3a\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>",
+        1 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#2d1b4e\"/><stop offset=\"50%\" stop-color=\"#4a2c5a\"/><stop offset=\"100%\" stop-color=\"#1a0f2e\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>",
+        20 => "<defs><radialGradient id=\"bg\"><stop offset=\"0%\" stop-color=\"#1a0033\"/><stop offset=\"100%\" stop-color=\"#330066\"/></radialGradient></defs><rect width=\"420\" height=\"420\" fill=\"url(#bg)\"/>",
+        _ => "<rect width=\"420\" height=\"420\" fill=\"#0a0a0a\"/>",
+    }
+}
+
+// Byte-for-byte port of zkBgNFT.generateCompleteSVG for a minted galaxy.
+// The contract overwrites backgroundType with (seed / 7) % 21, so it is derived here too.
+// Err carries the revert the contract would raise instead of returning.
+pub fn render_onchain_svg(seed: u64, spiral_type: u64, num_arms: u64, packed_triangles: &[u8]) -> Result<String, String> {
+    let background_type = generate_background_type(seed);
+    let mut svg = String::from("<svg width=\"420\" height=\"420\" xmlns=\"http://www.w3.org/2000/svg\">");
+    svg.push_str(onchain_background(background_type));
+
+    for triangle in unpack_triangles(packed_triangles).iter().take(ONCHAIN_TRIANGLE_LIMIT) {
+        let [(x1, y1), (x2, y2), (x3, y3)] = triangle.packed_vertices;
+        svg.push_str(&format!(
+            "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" opacity=\"0.7\"/>",
+            scale_coordinate(x1), scale_coordinate(y1),
+            scale_coordinate(x2), scale_coordinate(y2),
+            scale_coordinate(x3), scale_coordinate(y3),
+            ONCHAIN_ARM_COLORS[triangle.arm_index as usize % ONCHAIN_ARM_COLORS.len()]
+        ));
+    }
+
+    // _generateTitle indexes fixed-size memory arrays, which panics (0x32) out of range
+    let spiral_name = ONCHAIN_SPIRAL_TYPES.get(spiral_type as usize)
+        .ok_or_else(|| format!("Panic(0x32): spiralTypes[{}] out of bounds", spiral_type))?;
+    let background_name = ONCHAIN_BACKGROUND_NAMES.get(background_type as usize)
+        .ok_or_else(|| format!("Panic(0x32): backgroundTypes[{}] out of bounds", background_type))?;
+
+    svg.push_str(&format!(
+        "<text x=\"10\" y=\"410\" fill=\"white\" font-family=\"monospace\" font-size=\"10\">Seed: {} | {} | {} | Arms: {} | ZK Verified</text>",
+        seed, spiral_name, background_name, num_arms
+    ));
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvgDiffLine {
+    Same(String),
+    OffchainOnly(String),
+    OnchainOnly(String),
+}
+
+// Split into tags and the text between them, one diffable unit each
+fn svg_elements(svg: &str) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut rest = svg;
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            rest.find('>').map_or(rest.len(), |i| i + 1)
+        } else {
+            rest.find('<').unwrap_or(rest.len())
+        };
+        elements.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    elements
+}
+
+// Element-level LCS diff between an off-chain preview and the on-chain render
+pub fn diff_svg(offchain: &str, onchain: &str) -> Vec<SvgDiffLine> {
+    let a = svg_elements(offchain);
+    let b = svg_elements(onchain);
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            diff.push(SvgDiffLine::Same(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(SvgDiffLine::OffchainOnly(a[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(SvgDiffLine::OnchainOnly(b[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().map(|e| SvgDiffLine::OffchainOnly(e.to_string())));
+    diff.extend(b[j..].iter().map(|e| SvgDiffLine::OnchainOnly(e.to_string())));
+    diff
+}
@@ -0,0 +1,446 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::config::ServerConfig;
+use crate::gas::{FALLBACK_BTC_PRICE_USD, FALLBACK_ETH_PRICE_USD, FALLBACK_GAS_PRICE_GWEI};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UsdPrices {
+    pub eth: f64,
+    pub btc: f64,
+}
+
+// A gas price and/or fiat price provider. Sources only implement what they actually know;
+// the defaults report the capability as missing.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn gas_price_gwei(&self) -> Result<f64> {
+        bail!("{} does not provide gas prices", self.name())
+    }
+
+    async fn usd_prices(&self) -> Result<UsdPrices> {
+        bail!("{} does not provide USD prices", self.name())
+    }
+}
+
+// Etherscan gas oracle (FastGasPrice)
+pub struct EtherscanSource {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl EtherscanSource {
+    pub fn new(client: reqwest::Client, api_key: impl Into<String>) -> Self {
+        EtherscanSource { client, api_key: api_key.into() }
+    }
+}
+
+#[derive(Deserialize)]
+struct EtherscanGasResponse {
+    result: EtherscanGasResult,
+}
+
+#[derive(Deserialize)]
+struct EtherscanGasResult {
+    #[serde(rename = "FastGasPrice")]
+    fast_gas_price: String,
+}
+
+#[async_trait]
+impl PriceSource for EtherscanSource {
+    fn name(&self) -> &'static str {
+        "etherscan"
+    }
+
+    async fn gas_price_gwei(&self) -> Result<f64> {
+        let response = self.client
+            .get("https://api.etherscan.io/api")
+            .query(&[("module", "gastracker"), ("action", "gasoracle"), ("apikey", self.api_key.as_str())])
+            .send()
+            .await?
+            .error_for_status()?;
+        let gas_data: EtherscanGasResponse = response.json().await?;
+        Ok(gas_data.result.fast_gas_price.parse::<f64>()?)
+    }
+}
+
+// CoinGecko simple price API
+pub struct CoinGeckoSource {
+    client: reqwest::Client,
+}
+
+impl CoinGeckoSource {
+    pub fn new(client: reqwest::Client) -> Self {
+        CoinGeckoSource { client }
+    }
+}
+
+#[derive(Deserialize)]
+struct CoinGeckoResponse {
+    ethereum: CurrencyPrice,
+    bitcoin: CurrencyPrice,
+}
+
+#[derive(Deserialize)]
+struct CurrencyPrice {
+    usd: f64,
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn usd_prices(&self) -> Result<UsdPrices> {
+        let response = self.client
+            .get("https://api.coingecko.com/api/v3/simple/price?ids=ethereum,bitcoin&vs_currencies=usd")
+            .send()
+            .await?
+            .error_for_status()?;
+        let price_data: CoinGeckoResponse = response.json().await?;
+        Ok(UsdPrices { eth: price_data.ethereum.usd, btc: price_data.bitcoin.usd })
+    }
+}
+
+// Operator-maintained JSON file, re-read on every request so edits apply without a restart:
+// {"gas_price_gwei": 25.0, "eth_price_usd": 3100.0, "btc_price_usd": 60000.0}
+pub struct StaticFileSource {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct StaticPrices {
+    gas_price_gwei: Option<f64>,
+    eth_price_usd: Option<f64>,
+    btc_price_usd: Option<f64>,
+}
+
+impl StaticFileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        StaticFileSource { path: path.into() }
+    }
+
+    async fn read(&self) -> Result<StaticPrices> {
+        let text = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("reading {}", self.path.display()))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticFileSource {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn gas_price_gwei(&self) -> Result<f64> {
+        self.read().await?.gas_price_gwei
+            .ok_or_else(|| anyhow!("{} has no gas_price_gwei", self.path.display()))
+    }
+
+    async fn usd_prices(&self) -> Result<UsdPrices> {
+        let prices = self.read().await?;
+        match (prices.eth_price_usd, prices.btc_price_usd) {
+            (Some(eth), Some(btc)) => Ok(UsdPrices { eth, btc }),
+            _ => bail!("{} needs both eth_price_usd and btc_price_usd", self.path.display()),
+        }
+    }
+}
+
+// Any Ethereum node's eth_gasPrice (e.g. a local hardhat node)
+pub struct JsonRpcSource {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl JsonRpcSource {
+    pub fn new(client: reqwest::Client, url: impl Into<String>) -> Self {
+        JsonRpcSource { client, url: url.into() }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    result: Option<String>,
+    error: Option<serde_json::Value>,
+}
+
+#[async_trait]
+impl PriceSource for JsonRpcSource {
+    fn name(&self) -> &'static str {
+        "json-rpc"
+    }
+
+    async fn gas_price_gwei(&self) -> Result<f64> {
+        let request = serde_json::json!({"jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": 1});
+        let response: JsonRpcResponse = self.client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let wei_hex = match (response.result, response.error) {
+            (Some(result), _) => result,
+            (None, Some(error)) => bail!("eth_gasPrice failed: {}", error),
+            (None, None) => bail!("eth_gasPrice returned no result"),
+        };
+        let wei = u128::from_str_radix(wei_hex.trim_start_matches("0x"), 16)?;
+        Ok(wei as f64 / 1_000_000_000.0)
+    }
+}
+
+// Fixed values for tests and offline demos. `error` simulates an outage, and `calls`
+// counts upstream requests (clone the Arc before boxing to watch it).
+pub struct MockPriceSource {
+    pub gas_price_gwei: f64,
+    pub usd: UsdPrices,
+    pub error: Option<String>,
+    pub calls: Arc<AtomicUsize>,
+}
+
+impl Default for MockPriceSource {
+    fn default() -> Self {
+        MockPriceSource {
+            gas_price_gwei: FALLBACK_GAS_PRICE_GWEI,
+            usd: UsdPrices { eth: FALLBACK_ETH_PRICE_USD, btc: FALLBACK_BTC_PRICE_USD },
+            error: None,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl MockPriceSource {
+    fn answer<T>(&self, value: T) -> Result<T> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        match &self.error {
+            Some(error) => bail!("{}", error),
+            None => Ok(value),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for MockPriceSource {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn gas_price_gwei(&self) -> Result<f64> {
+        self.answer(self.gas_price_gwei)
+    }
+
+    async fn usd_prices(&self) -> Result<UsdPrices> {
+        self.answer(self.usd)
+    }
+}
+
+// Upstream calls give up after this, so a slow API can't stall /api/gas
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Which source answered, whether the hardcoded fallback was used instead,
+// and how old the value is (0 when fetched for this request)
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceReport {
+    pub source: String,
+    pub fallback: bool,
+    pub error: Option<String>,
+    pub age_ms: u64,
+}
+
+impl SourceReport {
+    fn from_result<T>(source: &dyn PriceSource, result: &Result<T>) -> Self {
+        SourceReport {
+            source: source.name().to_string(),
+            fallback: result.is_err(),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+            age_ms: 0,
+        }
+    }
+}
+
+// One client (and connection pool) for every upstream price API
+pub fn http_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .user_agent(concat!("zkbg/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+// Settings the sources are built from
+#[derive(Debug, Clone)]
+pub struct PriceSourceOptions {
+    pub etherscan_api_key: Option<String>,
+    pub rpc_url: String,
+    pub static_file: PathBuf,
+}
+
+impl Default for PriceSourceOptions {
+    fn default() -> Self {
+        PriceSourceOptions {
+            etherscan_api_key: None,
+            rpc_url: "http://127.0.0.1:8545".to_string(),
+            static_file: PathBuf::from("prices.json"),
+        }
+    }
+}
+
+pub fn price_source_by_name(name: &str, client: &reqwest::Client, options: &PriceSourceOptions) -> Result<Box<dyn PriceSource>> {
+    Ok(match name {
+        "etherscan" => match &options.etherscan_api_key {
+            Some(key) => Box::new(EtherscanSource::new(client.clone(), key.clone())),
+            None => bail!("the etherscan source needs an API key"),
+        },
+        "coingecko" => Box::new(CoinGeckoSource::new(client.clone())),
+        "static" => Box::new(StaticFileSource::new(options.static_file.clone())),
+        "json-rpc" | "rpc" => Box::new(JsonRpcSource::new(client.clone(), options.rpc_url.clone())),
+        "mock" => Box::new(MockPriceSource::default()),
+        other => bail!("unknown price source '{}' (etherscan, coingecko, static, json-rpc, mock)", other),
+    })
+}
+
+// The gas price source and the USD price source, chosen independently
+pub struct PriceSources {
+    pub gas: Box<dyn PriceSource>,
+    pub usd: Box<dyn PriceSource>,
+}
+
+impl PriceSources {
+    pub fn by_name(gas: &str, usd: &str, options: &PriceSourceOptions) -> Result<Self> {
+        let client = http_client()?;
+        Ok(PriceSources {
+            gas: price_source_by_name(gas, &client, options)?,
+            usd: price_source_by_name(usd, &client, options)?,
+        })
+    }
+
+    pub fn from_config(config: &ServerConfig) -> Result<Self> {
+        Self::by_name(config.gas_source(), &config.prices.usd_source, &config.price_source_options())
+    }
+
+    pub async fn gas_price_gwei(&self) -> (f64, SourceReport) {
+        let result = self.gas.gas_price_gwei().await;
+        let report = SourceReport::from_result(self.gas.as_ref(), &result);
+        (result.unwrap_or(FALLBACK_GAS_PRICE_GWEI), report)
+    }
+
+    pub async fn usd_prices(&self) -> (UsdPrices, SourceReport) {
+        let result = self.usd.usd_prices().await;
+        let report = SourceReport::from_result(self.usd.as_ref(), &result);
+        let fallback = UsdPrices { eth: FALLBACK_ETH_PRICE_USD, btc: FALLBACK_BTC_PRICE_USD };
+        (result.unwrap_or(fallback), report)
+    }
+}
+
+struct CachedQuote<T> {
+    value: T,
+    report: SourceReport,
+    fetched: Instant, // When the value was fetched, for age_ms
+    checked: Instant, // Last upstream attempt, successful or not; the TTL runs from here
+}
+
+impl<T: Copy> CachedQuote<T> {
+    fn reply(&self) -> (T, SourceReport) {
+        let mut report = self.report.clone();
+        report.age_ms = self.fetched.elapsed().as_millis() as u64;
+        (self.value, report)
+    }
+}
+
+// A real upstream answer replaces whatever is cached. When a refresh fails, the last good
+// value (now stale, with the error attached) beats the hardcoded fallback. Failures still
+// count as a check, so a source that is down is asked at most once per TTL instead of
+// making every request wait out its timeout.
+fn store_quote<T: Copy>(slot: &mut Option<CachedQuote<T>>, value: T, report: SourceReport) -> (T, SourceReport) {
+    let now = Instant::now();
+    match slot {
+        Some(cached) if report.fallback => {
+            cached.report.error = report.error;
+            cached.checked = now;
+            cached.reply()
+        }
+        _ => {
+            *slot = Some(CachedQuote { value, report: report.clone(), fetched: now, checked: now });
+            (value, report)
+        }
+    }
+}
+
+// Single-flight: concurrent requests for an expired quote wait for one upstream call
+async fn cached_quote<T: Copy>(
+    slot: &Mutex<Option<CachedQuote<T>>>,
+    ttl: Duration,
+    fetch: impl Future<Output = (T, SourceReport)>,
+) -> (T, SourceReport) {
+    let mut slot = slot.lock().await;
+    if let Some(cached) = slot.as_ref().filter(|cached| cached.checked.elapsed() < ttl) {
+        return cached.reply();
+    }
+    let (value, report) = fetch.await;
+    store_quote(&mut *slot, value, report)
+}
+
+// Price sources behind a TTL cache so /api/gas can be polled without hammering
+// Etherscan/CoinGecko. A TTL of zero disables caching.
+pub struct PriceCache {
+    sources: PriceSources,
+    ttl: Duration,
+    gas: Mutex<Option<CachedQuote<f64>>>,
+    usd: Mutex<Option<CachedQuote<UsdPrices>>>,
+}
+
+impl PriceCache {
+    pub fn new(sources: PriceSources, ttl: Duration) -> Self {
+        PriceCache { sources, ttl, gas: Mutex::new(None), usd: Mutex::new(None) }
+    }
+
+    pub fn sources(&self) -> &PriceSources {
+        &self.sources
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub async fn gas_price_gwei(&self) -> (f64, SourceReport) {
+        cached_quote(&self.gas, self.ttl, self.sources.gas_price_gwei()).await
+    }
+
+    pub async fn usd_prices(&self) -> (UsdPrices, SourceReport) {
+        cached_quote(&self.usd, self.ttl, self.sources.usd_prices()).await
+    }
+
+    // Fetch both quotes regardless of age. Upstream calls run outside the locks,
+    // so requests keep getting the previous values meanwhile.
+    pub async fn refresh(&self) {
+        let ((gas, gas_report), (usd, usd_report)) =
+            tokio::join!(self.sources.gas_price_gwei(), self.sources.usd_prices());
+        store_quote(&mut *self.gas.lock().await, gas, gas_report);
+        store_quote(&mut *self.usd.lock().await, usd, usd_report);
+    }
+
+    // Refresh now and then every `interval` for the life of the server
+    pub fn spawn_refresh(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.refresh().await;
+            }
+        })
+    }
+}
@@ -0,0 +1,103 @@
+// Seeded procedural background layers: star scatter, nebula clouds and dust lanes.
+// Integer-only, on the trig tables, like calculate_spiral_point, so a contract port
+// reproduces them exactly. All coordinates are in the background reference frame.
+use crate::backgrounds::SeedRng;
+use crate::generation::{COS_TABLE, SCALE_FACTOR, SIN_TABLE, TRIG_TABLE_SIZE};
+
+// Every layer is a set of translucent circles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dot {
+    pub x: u64,
+    pub y: u64,
+    pub radius: u64,
+    pub color: &'static str,
+    pub opacity: u8, // percent
+}
+
+// White, blue-white, yellow-white, orange
+pub const STAR_COLORS: [&str; 4] = ["#ffffff", "#cad7ff", "#fff4e8", "#ffd2a1"];
+
+// Quarter turn on the 32-entry trig table
+const QUARTER_TURN: usize = TRIG_TABLE_SIZE / 4;
+
+// Move `length` from (x, y) at a trig-table angle, then keep a circle of `radius` on the frame.
+// Same sign handling as trace_spiral_point: |d| = floor(length * |trig| / 10000).
+fn step(x: u64, y: u64, angle_index: usize, length: u64, radius: u64, frame: u64) -> (u64, u64) {
+    let sin_val = SIN_TABLE[angle_index % TRIG_TABLE_SIZE];
+    let cos_val = COS_TABLE[angle_index % TRIG_TABLE_SIZE];
+    let dx = length * cos_val.unsigned_abs() / SCALE_FACTOR as u64;
+    let dy = length * sin_val.unsigned_abs() / SCALE_FACTOR as u64;
+
+    let x = if cos_val < 0 { x.saturating_sub(dx) } else { x + dx };
+    let y = if sin_val < 0 { y.saturating_sub(dy) } else { y + dy };
+    (x.clamp(radius, frame - radius), y.clamp(radius, frame - radius))
+}
+
+// Mostly faint 1px points, some full-brightness ones and a few larger bright stars
+pub fn star_scatter(rng: &mut SeedRng, count: u64, frame: u64) -> Vec<Dot> {
+    (0..count)
+        .map(|_| {
+            let x = rng.between(1, frame - 1);
+            let y = rng.between(1, frame - 1);
+            let color = STAR_COLORS[rng.below(STAR_COLORS.len() as u64) as usize];
+            let (radius, opacity) = match rng.below(100) {
+                0..=74 => (1, 30 + rng.below(40) as u8),
+                75..=94 => (1, 100),
+                _ => (2, 100),
+            };
+            Dot { x: x.clamp(radius, frame - radius), y: y.clamp(radius, frame - radius), radius, color, opacity }
+        })
+        .collect()
+}
+
+// Each cloud is a random walk of overlapping puffs: the heading drifts a few table steps per
+// puff and the puffs shrink along the walk, which reads as soft noise at low opacity.
+pub fn nebula_clouds(
+    rng: &mut SeedRng,
+    clouds: u64,
+    puffs: u64,
+    palette: &'static [&'static str],
+    opacity: u8,
+    frame: u64,
+) -> Vec<Dot> {
+    let mut dots = Vec::with_capacity((clouds * puffs) as usize);
+    for cloud in 0..clouds {
+        let color = palette[cloud as usize % palette.len()];
+        let max_radius = frame / 7;
+        let mut x = rng.between(frame / 4, frame - frame / 4);
+        let mut y = rng.between(frame / 4, frame - frame / 4);
+        let mut heading = rng.below(TRIG_TABLE_SIZE as u64) as usize;
+
+        for puff in 0..puffs {
+            let radius = (max_radius - max_radius * puff / (2 * puffs)).max(2);
+            let (px, py) = (x.clamp(radius, frame - radius), y.clamp(radius, frame - radius));
+            dots.push(Dot { x: px, y: py, radius, color, opacity });
+
+            // Turn by -3..=3 table steps, then move about half a radius
+            heading = (heading + TRIG_TABLE_SIZE + rng.below(7) as usize - 3) % TRIG_TABLE_SIZE;
+            (x, y) = step(px, py, heading, radius / 2 + rng.below(radius / 2 + 1), 0, frame);
+        }
+    }
+    dots
+}
+
+// A dark band across the frame at a seeded angle, its puffs jittered sideways
+pub fn dust_lane(rng: &mut SeedRng, puffs: u64, width: u64, color: &'static str, opacity: u8, frame: u64) -> Vec<Dot> {
+    let center = frame / 2;
+    let angle = rng.below(TRIG_TABLE_SIZE as u64) as usize;
+    let span = frame * 9 / 10;
+    // Start half a span behind the centre (opposite heading), then walk forward
+    let (start_x, start_y) = step(center, center, angle + TRIG_TABLE_SIZE / 2, span / 2, 0, frame);
+    let spacing = span / puffs.max(1);
+
+    (0..puffs)
+        .map(|i| {
+            let radius = rng.between(width / 2, width).max(1);
+            let (x, y) = step(start_x, start_y, angle, i * spacing, 0, frame);
+            // Sideways jitter: a quarter turn either way
+            let side = if rng.below(2) == 0 { angle + QUARTER_TURN } else { angle + 3 * QUARTER_TURN };
+            let (x, y) = step(x, y, side, rng.below(width / 2 + 1), radius, frame);
+            Dot { x, y, radius, color, opacity }
+        })
+        .collect()
+}
@@ -0,0 +1,274 @@
+use halo2_proofs::{
+    halo2curves::pasta::{EqAffine, Fp},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::rngs::OsRng;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::circuits::simple_spirals::{calculate_public_inputs, circuit_k, SimpleSpiralsCircuit};
+use crate::generation::{calculate_configuration_mapping, generate_galaxy};
+use crate::keystore::{KeyStore, KeyStoreError};
+
+// A serialized Halo2 proof for one seed
+#[derive(Debug, Clone)]
+pub struct SpiralProof {
+    pub seed: u64,
+    pub canvas_size: u64,
+    pub k: u32,
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<Fp>,
+}
+
+// Public inputs for a seed: seed, variant_id, spiral_type, num_arms, background_type, triangle_commitment
+pub fn spiral_public_inputs(seed: u64, canvas_size: u64) -> Vec<Fp> {
+    let galaxy = generate_galaxy(seed, canvas_size);
+
+    calculate_public_inputs(seed, &galaxy.triangle_vertices(), &galaxy.triangle_metadata())
+}
+
+// IPA parameter setup for Pasta (transparent, no trusted setup)
+pub fn setup_params(k: u32) -> ParamsIPA<EqAffine> {
+    ParamsIPA::<EqAffine>::new(k)
+}
+
+// Generate the proving key (which embeds the verifying key) from the circuit shape only.
+// The shape depends on num_arms, so keys are only valid for galaxies with the same arm count.
+pub fn generate_keys(
+    params: &ParamsIPA<EqAffine>,
+    circuit: &SimpleSpiralsCircuit<Fp>,
+) -> Result<ProvingKey<EqAffine>, Error> {
+    let empty_circuit = circuit.without_witnesses();
+    let vk = keygen_vk(params, &empty_circuit)?;
+    keygen_pk(params, vk, &empty_circuit)
+}
+
+// Same keys as generate_keys for any seed with num_arms arms, without needing a seed
+pub fn generate_keys_for_arms(params: &ParamsIPA<EqAffine>, num_arms: u64) -> Result<ProvingKey<EqAffine>, Error> {
+    let empty_circuit = SimpleSpiralsCircuit::<Fp>::keygen_shape(num_arms);
+    let vk = keygen_vk(params, &empty_circuit)?;
+    keygen_pk(params, vk, &empty_circuit)
+}
+
+// The verifying key alone, for verifiers: same vk as generate_keys_for_arms, without the pk
+pub fn generate_verifying_key_for_arms(params: &ParamsIPA<EqAffine>, num_arms: u64) -> Result<VerifyingKey<EqAffine>, Error> {
+    keygen_vk(params, &SimpleSpiralsCircuit::<Fp>::keygen_shape(num_arms))
+}
+
+// Create a proof and return the serialized transcript bytes
+pub fn create_spiral_proof(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: SimpleSpiralsCircuit<Fp>,
+    public_inputs: &[Fp],
+) -> Result<Vec<u8>, Error> {
+    let instances: &[&[Fp]] = &[public_inputs];
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+
+    create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        &mut transcript,
+    )?;
+
+    Ok(transcript.finalize())
+}
+
+// Verify serialized proof bytes against a verifying key
+pub fn verify_spiral_proof(
+    params: &ParamsIPA<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    public_inputs: &[Fp],
+) -> Result<(), Error> {
+    let instances: &[&[Fp]] = &[public_inputs];
+    let strategy = SingleStrategy::new(params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+
+    verify_proof::<IPACommitmentScheme<EqAffine>, VerifierIPA<_>, _, _, _>(
+        params,
+        vk,
+        strategy,
+        &[instances],
+        &mut transcript,
+    )
+}
+
+// Circuit size for the galaxy a seed produces
+pub fn seed_k(seed: u64) -> u32 {
+    let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed % 41);
+    circuit_k(num_arms)
+}
+
+// Params and proving key for one circuit shape (all seeds with num_arms arms)
+pub struct CircuitKeys {
+    pub num_arms: u64,
+    pub k: u32,
+    pub params: ParamsIPA<EqAffine>,
+    pub pk: ProvingKey<EqAffine>,
+}
+
+// Params and verifying key for one circuit shape: all verification needs
+pub struct VerifierKeys {
+    pub num_arms: u64,
+    pub params: ParamsIPA<EqAffine>,
+    pub vk: VerifyingKey<EqAffine>,
+}
+
+// One lock per circuit shape: the map lock is only held to find a shape's slot, so keygen
+// for one arm count never blocks requests for another
+type ShapeSlots<T> = Mutex<HashMap<u64, Arc<Mutex<Option<Arc<T>>>>>>;
+
+fn shape_slot<T>(slots: &ShapeSlots<T>, num_arms: u64) -> Arc<Mutex<Option<Arc<T>>>> {
+    let mut slots = slots.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    slots.entry(num_arms).or_default().clone()
+}
+
+// Keys generated (or loaded from the key store) on first use per arm count and reused by later requests
+#[derive(Default)]
+pub struct KeyCache {
+    keys: ShapeSlots<CircuitKeys>,
+    verifiers: ShapeSlots<VerifierKeys>,
+    store: Option<KeyStore>,
+}
+
+impl KeyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Persist generated keys in `store` and load them from there on later runs
+    pub fn with_store(store: KeyStore) -> Self {
+        KeyCache {
+            keys: Mutex::default(),
+            verifiers: Mutex::default(),
+            store: Some(store),
+        }
+    }
+
+    // Returns (keys, was_cached). Keygen runs under the shape's lock so concurrent
+    // requests for the same shape don't generate the keys twice; other shapes aren't held up.
+    pub fn get_or_generate(&self, num_arms: u64) -> Result<(Arc<CircuitKeys>, bool), Error> {
+        let slot = shape_slot(&self.keys, num_arms);
+        let mut keys = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(cached) = keys.as_ref() {
+            return Ok((cached.clone(), true));
+        }
+
+        if let Some(store) = &self.store {
+            match store.load(num_arms) {
+                Ok(loaded) => {
+                    let loaded = Arc::new(loaded);
+                    *keys = Some(loaded.clone());
+                    return Ok((loaded, true));
+                }
+                Err(KeyStoreError::Missing) => {}
+                Err(e) => eprintln!("⚠️  Not loading {}-arm keys from {}: {}", num_arms, store.dir().display(), e),
+            }
+        }
+
+        let k = circuit_k(num_arms);
+        let params = setup_params(k);
+        let pk = generate_keys_for_arms(&params, num_arms)?;
+        let generated = Arc::new(CircuitKeys { num_arms, k, params, pk });
+
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(&generated) {
+                eprintln!("⚠️  Could not persist {}-arm keys to {}: {}", num_arms, store.dir().display(), e);
+            }
+        }
+
+        *keys = Some(generated.clone());
+        Ok((generated, false))
+    }
+
+    // Keys for the shape a seed produces
+    pub fn for_seed(&self, seed: u64) -> Result<(Arc<CircuitKeys>, bool), Error> {
+        let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed % 41);
+        self.get_or_generate(num_arms)
+    }
+
+    // Verifying key from memory, this shape's proving keys or the key store. A verifier-only
+    // deployment without key files generates just the vk, never the (much costlier) pk.
+    pub fn verifier(&self, num_arms: u64) -> Result<Arc<VerifierKeys>, Error> {
+        let slot = shape_slot(&self.verifiers, num_arms);
+        let mut verifier = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(cached) = verifier.as_ref() {
+            return Ok(cached.clone());
+        }
+
+        // try_lock: never wait behind a keygen holding this shape's proving-key lock
+        let proving_slot = shape_slot(&self.keys, num_arms);
+        let proving_keys = proving_slot.try_lock().ok().and_then(|keys| keys.clone());
+        let found = match (proving_keys, &self.store) {
+            (Some(keys), _) => Some(VerifierKeys {
+                num_arms,
+                params: keys.params.clone(),
+                vk: keys.pk.get_vk().clone(),
+            }),
+            (None, Some(store)) => match store.load_verifier(num_arms) {
+                Ok(loaded) => Some(loaded),
+                Err(KeyStoreError::Missing) => None,
+                Err(e) => {
+                    eprintln!("⚠️  Not loading {}-arm verifying key from {}: {}", num_arms, store.dir().display(), e);
+                    None
+                }
+            },
+            (None, None) => None,
+        };
+        let found = match found {
+            Some(found) => found,
+            None => {
+                let params = setup_params(circuit_k(num_arms));
+                let vk = generate_verifying_key_for_arms(&params, num_arms)?;
+                VerifierKeys { num_arms, params, vk }
+            }
+        };
+
+        let found = Arc::new(found);
+        *verifier = Some(found.clone());
+        Ok(found)
+    }
+}
+
+// Prove a seed with already generated keys
+pub fn prove_with_keys(keys: &CircuitKeys, seed: u64, canvas_size: u64) -> Result<SpiralProof, Error> {
+    let circuit = SimpleSpiralsCircuit::from_seed(seed, canvas_size);
+    let public_inputs = spiral_public_inputs(seed, canvas_size);
+    let proof = create_spiral_proof(&keys.params, &keys.pk, circuit, &public_inputs)?;
+
+    // create_proof doesn't check every constraint, so never hand out a proof that fails verification
+    verify_spiral_proof(&keys.params, keys.pk.get_vk(), &proof, &public_inputs)
+        .map_err(|_| Error::ConstraintSystemFailure)?;
+
+    Ok(SpiralProof {
+        seed,
+        canvas_size,
+        k: keys.k,
+        proof,
+        public_inputs,
+    })
+}
+
+// One-shot: setup, keygen and prove for a seed, with the same verify guard as prove_with_keys
+pub fn prove_seed(seed: u64, canvas_size: u64) -> Result<SpiralProof, Error> {
+    let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed % 41);
+    let k = circuit_k(num_arms);
+    let params = setup_params(k);
+    let pk = generate_keys_for_arms(&params, num_arms)?;
+    prove_with_keys(&CircuitKeys { num_arms, k, params, pk }, seed, canvas_size)
+}
@@ -0,0 +1,36 @@
+use anyhow::{anyhow, bail, Result};
+use resvg::{tiny_skia, usvg};
+
+use crate::generation::Galaxy;
+use crate::render::generate_complete_svg;
+
+// 4096² RGBA is 64 MiB of pixmap; anything larger is refused rather than allocated
+pub const MAX_PNG_CANVAS_SIZE: u64 = 4096;
+
+// What the PNG leaves out compared with the SVG export: the seed/arms caption, since <text>
+// isn't rendered. /api/export/png reports it in the x-zkbg-png-omits header.
+pub const PNG_OMITTED_ELEMENTS: &str = "title-text";
+
+// Rasterize an SVG document at its own width/height.
+// No fonts are loaded (resvg is built without text support), so the output never depends
+// on what the host has installed; <text> elements are skipped.
+pub fn render_svg_png(svg: &str) -> Result<Vec<u8>> {
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default())?;
+    let size = tree.size().to_int_size();
+    if u64::from(size.width().max(size.height())) > MAX_PNG_CANVAS_SIZE {
+        bail!("{}x{} exceeds the {}px PNG limit", size.width(), size.height(), MAX_PNG_CANVAS_SIZE);
+    }
+
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| anyhow!("cannot allocate a {}x{} pixmap", size.width(), size.height()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png()?)
+}
+
+// PNG of the same SVG /api/export/svg returns
+pub fn render_galaxy_png(galaxy: &Galaxy, include_background: bool) -> Result<Vec<u8>> {
+    if galaxy.canvas_size > MAX_PNG_CANVAS_SIZE {
+        bail!("canvas_size {} exceeds the {}px PNG limit", galaxy.canvas_size, MAX_PNG_CANVAS_SIZE);
+    }
+    render_svg_png(&generate_complete_svg(galaxy, include_background))
+}
@@ -0,0 +1,232 @@
+use crate::backgrounds::{background_spec, BaseFill, Feature, SeedRng, BACKGROUNDS, REFERENCE_FRAME};
+use crate::generation::{Galaxy, COS_TABLE, SCALE_FACTOR, SIN_TABLE, TRIG_TABLE_SIZE};
+use crate::procedural::{dust_lane, nebula_clouds, star_scatter, Dot};
+use crate::svg::{circle, galaxy_namespace, gradient, line, path, polygon, rect, url, Element, GradientKind, SvgDocument};
+
+// UPDATED: 21 background types (0-20), named by the background table
+pub const BACKGROUND_NAMES: [&str; 21] = background_names();
+
+const fn background_names() -> [&'static str; 21] {
+    let mut names = [""; 21];
+    let mut i = 0;
+    while i < names.len() {
+        names[i] = BACKGROUNDS[i].name;
+        i += 1;
+    }
+    names
+}
+
+pub const ARM_COLORS: [&str; 8] = ["#ff6b6b", "#4ecdc4", "#45b7d1", "#96ceb4", "#feca57", "#ff9ff3", "#a8e6cf", "#ff8b94"];
+
+pub fn background_name(background_type: u64) -> &'static str {
+    BACKGROUND_NAMES.get(background_type as usize).copied().unwrap_or("Unknown")
+}
+
+// Generate complete SVG with background and optimized triangles.
+// IDs are namespaced by seed and canvas size, so several galaxies can share one HTML page.
+pub fn generate_complete_svg(galaxy: &Galaxy, include_background: bool) -> String {
+    let canvas_size = galaxy.canvas_size;
+    let mut doc = SvgDocument::new(canvas_size, canvas_size, galaxy_namespace(galaxy.seed, canvas_size));
+
+    // Add background if requested
+    if include_background {
+        draw_background(&mut doc, galaxy.background_type, galaxy.seed);
+    } else {
+        doc.push(rect(canvas_size, canvas_size).attr("fill", "#0a0a0a"));
+    }
+
+    // Add triangles
+    for triangle in &galaxy.triangles {
+        let color = ARM_COLORS[triangle.arm_index as usize % ARM_COLORS.len()];
+        doc.push(polygon(&triangle.vertices).attr("fill", color).attr("opacity", "0.7"));
+    }
+
+    // Add title
+    let spiral_types = ["Tight", "Loose", "Classic"];
+    let title = format!(
+        "Seed: {} | {} {} | {} | Arms: {} | ZK Verified",
+        galaxy.seed,
+        spiral_types[galaxy.spiral_type as usize],
+        "Galaxy",
+        background_name(galaxy.background_type),
+        galaxy.num_arms
+    );
+    doc.push(
+        Element::new("text")
+            .attr("x", 10)
+            .attr("y", canvas_size.saturating_sub(10))
+            .attr("fill", "white")
+            .attr("font-family", "monospace")
+            .attr("font-size", 10)
+            .text(title),
+    );
+
+    doc.render()
+}
+
+fn opacity(percent: u8) -> String {
+    if percent >= 100 { "1".to_string() } else { format!("0.{:02}", percent) }
+}
+
+// Point `length` away from (x, y) at a trig-table angle, same integer math as calculate_spiral_point
+fn offset_point(x: i64, y: i64, angle_index: usize, length: u64) -> (i64, i64) {
+    let length = length as i64;
+    (
+        x + length * COS_TABLE[angle_index % TRIG_TABLE_SIZE] / SCALE_FACTOR,
+        y + length * SIN_TABLE[angle_index % TRIG_TABLE_SIZE] / SCALE_FACTOR,
+    )
+}
+
+// Maps reference-frame geometry (the 420px layout in the background table) onto the canvas
+#[derive(Debug, Clone, Copy)]
+struct CanvasScale {
+    canvas_size: u64,
+}
+
+impl CanvasScale {
+    // Positions: exact at 420, proportional elsewhere
+    fn pos(&self, v: i64) -> i64 {
+        v * self.canvas_size as i64 / REFERENCE_FRAME as i64
+    }
+
+    // Radii and stroke widths never shrink to nothing on small canvases
+    fn len(&self, v: u64) -> u64 {
+        (v * self.canvas_size / REFERENCE_FRAME).max(1)
+    }
+
+    fn line(&self, (x1, y1): (i64, i64), (x2, y2): (i64, i64), color: &str, width: u64, pct: u8) -> Element {
+        line(self.pos(x1), self.pos(y1), self.pos(x2), self.pos(y2))
+            .attr("stroke", color)
+            .attr("stroke-width", self.len(width))
+            .attr("opacity", opacity(pct))
+    }
+
+    fn dot(&self, dot: &Dot) -> Element {
+        circle(self.pos(dot.x as i64), self.pos(dot.y as i64), self.len(dot.radius))
+            .attr("fill", dot.color)
+            .attr("opacity", opacity(dot.opacity))
+    }
+}
+
+// Background fragment (base fill + overlays) for a background type, seeded per galaxy and
+// sized to the canvas. The seeded layout is the same at every size, only scaled.
+pub fn background_svg(background_type: u64, seed: u64, canvas_size: u64) -> String {
+    let mut doc = SvgDocument::new(canvas_size, canvas_size, galaxy_namespace(seed, canvas_size));
+    draw_background(&mut doc, background_type, seed);
+    doc.render_inner()
+}
+
+fn draw_background(doc: &mut SvgDocument, background_type: u64, seed: u64) {
+    let canvas_size = doc.width();
+    let frame = REFERENCE_FRAME;
+    let scale = CanvasScale { canvas_size };
+    let spec = match background_spec(background_type) {
+        Some(spec) => spec,
+        None => {
+            doc.push(rect(canvas_size, canvas_size).attr("fill", "#0a0a0a"));
+            return;
+        }
+    };
+
+    let mut define_gradient = |kind, stops: &[(u8, &str)]| {
+        let id = doc.id("bg");
+        doc.define(gradient(kind, &id, stops));
+        url(&id)
+    };
+    let base_fill = match spec.base {
+        BaseFill::Solid(color) => color.to_string(),
+        BaseFill::Radial(stops) => define_gradient(GradientKind::Radial, stops),
+        BaseFill::Linear(stops) => define_gradient(GradientKind::Linear, stops),
+    };
+    doc.push(rect(canvas_size, canvas_size).attr("fill", base_fill));
+
+    // Layouts are drawn in reference-frame units and scaled only when written out
+    let center = (frame / 2) as i64;
+    let c = scale.pos(center);
+    for (stream, feature) in spec.features.iter().enumerate() {
+        let mut rng = SeedRng::new(seed, stream as u64);
+        match *feature {
+            Feature::Disc { radius, color, opacity: pct } => {
+                doc.push(circle(c, c, scale.len(radius)).attr("fill", color).attr("opacity", opacity(pct)))
+            }
+            Feature::Rings { radii, width, color, opacity: pct } => {
+                for &radius in radii {
+                    doc.push(
+                        circle(c, c, scale.len(radius))
+                            .attr("fill", "none")
+                            .attr("stroke", color)
+                            .attr("stroke-width", scale.len(width))
+                            .attr("opacity", opacity(pct)),
+                    );
+                }
+            }
+            Feature::Rays { count, length, width, color, opacity: pct } => {
+                let start = rng.below(TRIG_TABLE_SIZE as u64) as usize;
+                let step = TRIG_TABLE_SIZE / count.max(1) as usize;
+                for i in 0..count as usize {
+                    let end = offset_point(center, center, start + i * step, length);
+                    doc.push(scale.line((center, center), end, color, width, pct));
+                }
+            }
+            Feature::Streaks { count, length, width, color, opacity: pct } => {
+                // Starts are inset by the length so every streak ends inside the canvas
+                let angle = rng.below(TRIG_TABLE_SIZE as u64) as usize;
+                for _ in 0..count {
+                    let x = rng.between(length, frame - length) as i64;
+                    let y = rng.between(length, frame - length) as i64;
+                    doc.push(scale.line((x, y), offset_point(x, y, angle, length), color, width, pct));
+                }
+            }
+            Feature::Scatter { count, min_radius, max_radius, color, opacity: pct } => {
+                for _ in 0..count {
+                    let radius = rng.between(min_radius, max_radius);
+                    let x = rng.between(radius, frame - radius);
+                    let y = rng.between(radius, frame - radius);
+                    doc.push(scale.dot(&Dot { x, y, radius, color, opacity: pct }));
+                }
+            }
+            Feature::Starfield { count } => {
+                for dot in star_scatter(&mut rng, count, frame) {
+                    doc.push(scale.dot(&dot));
+                }
+            }
+            Feature::Nebula { clouds, puffs, palette, opacity: pct } => {
+                for dot in nebula_clouds(&mut rng, clouds, puffs, palette, pct, frame) {
+                    doc.push(scale.dot(&dot));
+                }
+            }
+            Feature::DustLane { puffs, width, color, opacity: pct } => {
+                for dot in dust_lane(&mut rng, puffs, width, color, pct, frame) {
+                    doc.push(scale.dot(&dot));
+                }
+            }
+            Feature::Bands { count, width, color, opacity: pct } => {
+                // The control point stays inside the canvas, so the whole curve does too
+                for _ in 0..count {
+                    let y = rng.between(frame / 7, frame - frame / 7) as i64;
+                    let sag = rng.between(0, 2 * (frame / 7)) as i64 - (frame / 7) as i64;
+                    let d = format!("M0 {} Q{} {} {} {}", scale.pos(y), c, scale.pos(y + sag), canvas_size, scale.pos(y));
+                    doc.push(
+                        path(d)
+                            .attr("fill", "none")
+                            .attr("stroke", color)
+                            .attr("stroke-width", scale.len(width))
+                            .attr("opacity", opacity(pct)),
+                    );
+                }
+            }
+            Feature::Web { nodes, color, opacity: pct } => {
+                let margin = frame / 20;
+                let points: Vec<(i64, i64)> = (0..nodes)
+                    .map(|_| (rng.between(margin, frame - margin) as i64, rng.between(margin, frame - margin) as i64))
+                    .collect();
+                for (i, &(x, y)) in points.iter().enumerate() {
+                    for next in [1, 3] {
+                        doc.push(scale.line((x, y), points[(i + next) % points.len()], color, 1, pct));
+                    }
+                    doc.push(circle(scale.pos(x), scale.pos(y), scale.len(2)).attr("fill", color));
+                }
+            }
+        }
+    }
+}
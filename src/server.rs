@@ -0,0 +1,410 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use warp::http::StatusCode;
+use warp::{Filter, Reply};
+
+use crate::api::{
+    calldata_response, canvas_size_or, hex_to_field, prove_response, public_input_data, public_input_mismatches,
+    spiral_response, unix_timestamp, CalldataRequest, ErrorResponse, GasSimulationRequest, GasRequest, PngExportRequest, ProveRequest, SVGExportInfo,
+    SVGExportRequest, SVGExportResponse, SpiralRequest, VerifyRequest, VerifyResponse,
+};
+use crate::config::ServerConfig;
+use crate::contract::{parse_address, parse_bytes32, MAX_TRIANGLES_PER_BATCH};
+use crate::evm_sim::{simulate_mint_gas, ContractArtifact};
+use crate::gas::calculate_gas_costs;
+use crate::generation::{calculate_configuration_mapping, generate_galaxy};
+use crate::prices::PriceCache;
+use crate::prover::{prove_with_keys, spiral_public_inputs, verify_spiral_proof, KeyCache};
+use crate::raster::{render_galaxy_png, PNG_OMITTED_ELEMENTS};
+use crate::render::generate_complete_svg;
+
+// Gas units are measured in revm when the contract artifact is loaded, else estimated
+async fn handle_gas_estimation(
+    request: GasRequest,
+    prices: Arc<PriceCache>,
+    artifact: Arc<ContractArtifact>,
+    default_canvas: u64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let seed = request.seed.unwrap_or(12345u64);
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+
+    let simulated = match artifact.bytecode() {
+        Ok(bytecode) => tokio::task::spawn_blocking(move || {
+            simulate_mint_gas(&bytecode, &generate_galaxy(seed, canvas_size), MAX_TRIANGLES_PER_BATCH)
+                .map_err(|e| format!("gas simulation failed: {:#}", e))
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("simulation task failed: {}", e))),
+        Err(e) => Err(e),
+    };
+
+    let galaxy = generate_galaxy(seed, canvas_size);
+    let response = calculate_gas_costs(&galaxy, &prices, simulated).await;
+    Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+}
+
+async fn handle_generate_spiral(request: SpiralRequest, default_canvas: u64) -> Result<impl warp::Reply, warp::Rejection> {
+    let canvas_size = match canvas_size_or(Some(request.canvas_size), default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+    let galaxy = generate_galaxy(request.seed, canvas_size);
+    Ok(warp::reply::with_status(warp::reply::json(&spiral_response(&galaxy)), StatusCode::OK))
+}
+
+// Handle SVG export for gas estimation verification
+async fn handle_svg_export(request: SVGExportRequest, default_canvas: u64) -> Result<impl warp::Reply, warp::Rejection> {
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+    let include_background = request.include_background.unwrap_or(true);
+
+    // Generate spiral data and the complete SVG from the same galaxy
+    let galaxy = generate_galaxy(request.seed, canvas_size);
+    let svg_content = generate_complete_svg(&galaxy, include_background);
+
+    // Create export info
+    let export_info = SVGExportInfo {
+        canvas_size,
+        triangle_count: galaxy.triangles.len() as u64,
+        background_included: include_background,
+        file_size_bytes: svg_content.len(),
+        export_timestamp: unix_timestamp(),
+    };
+
+    let response = SVGExportResponse {
+        svg_content,
+        spiral_data: spiral_response(&galaxy),
+        export_info,
+    };
+
+    Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+}
+
+// Rasterized export for marketplaces and thumbnails; rendering is CPU-bound.
+// Unlike the SVG it has no title caption, which the x-zkbg-png-omits header says.
+async fn handle_png_export(request: PngExportRequest, default_canvas: u64) -> Result<warp::reply::Response, warp::Rejection> {
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e).into_response()),
+    };
+    let include_background = request.include_background.unwrap_or(true);
+    let seed = request.seed;
+
+    let result = tokio::task::spawn_blocking(move || {
+        render_galaxy_png(&generate_galaxy(seed, canvas_size), include_background)
+    })
+    .await;
+
+    let reply = match result {
+        Ok(Ok(png)) => warp::reply::with_header(
+            warp::reply::with_header(
+                warp::reply::with_header(png, "content-type", "image/png"),
+                "content-disposition",
+                format!("inline; filename=\"zkbg-{}-{}px.png\"", seed, canvas_size),
+            ),
+            "x-zkbg-png-omits",
+            PNG_OMITTED_ELEMENTS,
+        )
+        .into_response(),
+        Ok(Err(e)) => error_reply(StatusCode::BAD_REQUEST, format!("PNG export failed: {:#}", e)).into_response(),
+        Err(e) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("render task failed: {}", e)).into_response(),
+    };
+    Ok(reply)
+}
+
+// Measured (not estimated) mint gas: runs the compiled contract in an embedded EVM
+async fn handle_gas_simulation(
+    request: GasSimulationRequest,
+    artifact: Arc<ContractArtifact>,
+    default_canvas: u64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+    let bytecode = match artifact.bytecode() {
+        Ok(bytecode) => bytecode,
+        Err(e) => {
+            return Ok(error_reply(
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("gas simulation unavailable, no contract artifact loaded at startup: {}", e),
+            ))
+        }
+    };
+    let batch_size = request.batch_size.unwrap_or(MAX_TRIANGLES_PER_BATCH);
+    let seed = request.seed;
+
+    let result = tokio::task::spawn_blocking(move || {
+        simulate_mint_gas(&bytecode, &generate_galaxy(seed, canvas_size), batch_size)
+    })
+    .await;
+
+    let reply = match result {
+        Ok(Ok(simulated)) => warp::reply::with_status(warp::reply::json(&simulated), StatusCode::OK),
+        Ok(Err(e)) => error_reply(StatusCode::UNPROCESSABLE_ENTITY, format!("gas simulation failed: {:#}", e)),
+        Err(e) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("simulation task failed: {}", e)),
+    };
+    Ok(reply)
+}
+
+// ABI-encoded zkBgNFT calls for a seed, ready to submit as transaction data
+async fn handle_calldata(request: CalldataRequest, default_canvas: u64) -> Result<impl warp::Reply, warp::Rejection> {
+    let to = match parse_address(&request.to) {
+        Some(to) => to,
+        None => return Ok(error_reply(StatusCode::BAD_REQUEST, format!("invalid address: {}", request.to))),
+    };
+    let zk_proof_hash = match request.zk_proof_hash.as_deref() {
+        Some(hash) => match parse_bytes32(hash) {
+            Some(hash) => Some(hash),
+            None => return Ok(error_reply(StatusCode::BAD_REQUEST, format!("zk_proof_hash is not 32 bytes of hex: {}", hash))),
+        },
+        None => None,
+    };
+
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+    let galaxy = generate_galaxy(request.seed, canvas_size);
+    let response = calldata_response(
+        &galaxy,
+        to,
+        request.token_id,
+        zk_proof_hash,
+        request.token_uri.as_deref().unwrap_or(""),
+        request.batch_size.unwrap_or(MAX_TRIANGLES_PER_BATCH),
+    );
+    Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+}
+
+fn error_reply(status: StatusCode, error: String) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(warp::reply::json(&ErrorResponse { error }), status)
+}
+
+// Keygen and proving are CPU-bound, so they run off the async executor
+async fn handle_prove(
+    request: ProveRequest,
+    keys: Arc<KeyCache>,
+    default_canvas: u64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let seed = request.seed;
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        let (circuit_keys, key_cached) = keys.for_seed(seed)?;
+        let started = Instant::now();
+        let proof = prove_with_keys(&circuit_keys, seed, canvas_size)?;
+        let proving_time_ms = started.elapsed().as_millis() as u64;
+        let galaxy = generate_galaxy(seed, canvas_size);
+        Ok::<_, halo2_proofs::plonk::Error>(prove_response(&proof, &galaxy, proving_time_ms, key_cached))
+    })
+    .await;
+
+    let reply = match result {
+        Ok(Ok(response)) => warp::reply::with_status(warp::reply::json(&response), StatusCode::OK),
+        Ok(Err(e)) => error_reply(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("proof generation failed for seed {} on a {}px canvas: {:?}", seed, canvas_size, e),
+        ),
+        Err(e) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("prover task failed: {}", e)),
+    };
+    Ok(reply)
+}
+
+// Verify a proof against the verifying key for the seed's circuit shape
+async fn handle_verify(
+    request: VerifyRequest,
+    keys: Arc<KeyCache>,
+    default_canvas: u64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let proof = match hex::decode(request.proof.trim_start_matches("0x")) {
+        Ok(proof) => proof,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, format!("proof is not valid hex: {}", e))),
+    };
+    let canvas_size = match canvas_size_or(request.canvas_size, default_canvas) {
+        Ok(canvas_size) => canvas_size,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+    let expected = spiral_public_inputs(request.seed, canvas_size);
+    let mismatched_inputs = match public_input_mismatches(&request, &expected) {
+        Ok(mismatches) => mismatches,
+        Err(e) => return Ok(error_reply(StatusCode::BAD_REQUEST, e)),
+    };
+
+    // When the caller sent raw public inputs, also learn whether the proof is at least valid for those
+    let claimed_inputs: Option<Vec<_>> = request.public_inputs.as_ref()
+        .map(|values| values.iter().filter_map(|value| hex_to_field(value)).collect());
+    let seed = request.seed;
+    let (_, num_arms, _, _, _) = calculate_configuration_mapping(seed % 41);
+
+    // Verification needs only the vk: persisted, cached, or generated without a pk
+    let result = tokio::task::spawn_blocking(move || {
+        let verifier = keys.verifier(num_arms)?;
+        let proof_verified = verify_spiral_proof(&verifier.params, &verifier.vk, &proof, &expected).is_ok();
+        let verified_for_claim = !proof_verified
+            && claimed_inputs.is_some_and(|claimed| {
+                verify_spiral_proof(&verifier.params, &verifier.vk, &proof, &claimed).is_ok()
+            });
+        Ok::<_, halo2_proofs::plonk::Error>((proof_verified, verified_for_claim, expected))
+    })
+    .await;
+
+    let (proof_verified, verified_for_claim, expected) = match result {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(e)) => {
+            return Ok(error_reply(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("verifying key generation failed for {} arms (seed {}): {:?}", num_arms, seed, e),
+            ))
+        }
+        Err(e) => return Ok(error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("verifier task failed: {}", e))),
+    };
+
+    let reason = if !mismatched_inputs.is_empty() {
+        let names: Vec<&str> = mismatched_inputs.iter().map(|m| m.name.as_str()).collect();
+        Some(format!("claimed public inputs do not match seed {}: {}", seed, names.join(", ")))
+    } else if verified_for_claim {
+        Some(format!("proof is valid for the claimed public inputs, but those are not seed {}'s", seed))
+    } else if !proof_verified {
+        Some("proof does not verify against the expected public inputs".to_string())
+    } else {
+        None
+    };
+
+    let response = VerifyResponse {
+        valid: proof_verified && mismatched_inputs.is_empty(),
+        proof_verified,
+        mismatched_inputs,
+        reason,
+        expected_public_inputs: public_input_data(&expected),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+}
+
+// All HTTP routes (API, static visualizer, health) with CORS applied.
+// Proving keys, the price cache and the contract artifact are shared by every request; the config supplies
+// the default canvas, the static directory and the allowed origins.
+pub fn routes(
+    config: &ServerConfig,
+    keys: Arc<KeyCache>,
+    prices: Arc<PriceCache>,
+    artifact: Arc<ContractArtifact>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let with_keys = warp::any().map(move || keys.clone());
+    let with_prices = warp::any().map(move || prices.clone());
+    let with_artifact = warp::any().map(move || artifact.clone());
+    let default_canvas = config.default_canvas_size;
+    let with_canvas = warp::any().map(move || default_canvas);
+
+    // API route for generating OPTIMIZED galaxy spirals
+    let api_generate = warp::path("api")
+        .and(warp::path("generate"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_canvas.clone())
+        .and_then(handle_generate_spiral);
+
+    // API route for OPTIMIZED gas estimation
+    let api_gas = warp::path("api")
+        .and(warp::path("gas"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_prices)
+        .and(with_artifact.clone())
+        .and(with_canvas.clone())
+        .and_then(handle_gas_estimation);
+
+    // API route for EVM-measured mint gas
+    let api_gas_simulate = warp::path("api")
+        .and(warp::path("gas"))
+        .and(warp::path("simulate"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_artifact)
+        .and(with_canvas.clone())
+        .and_then(handle_gas_simulation);
+
+    // API route for SVG export (for verification during gas estimation)
+    let api_svg_export = warp::path("api")
+        .and(warp::path("export"))
+        .and(warp::path("svg"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_canvas.clone())
+        .and_then(handle_svg_export);
+
+    // API route for PNG export
+    let api_png_export = warp::path("api")
+        .and(warp::path("export"))
+        .and(warp::path("png"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_canvas.clone())
+        .and_then(handle_png_export);
+
+    // API route for zkBgNFT calldata (initializeGalaxy / addTriangleBatch / mintSpiral)
+    let api_calldata = warp::path("api")
+        .and(warp::path("calldata"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_canvas.clone())
+        .and_then(handle_calldata);
+
+    // API route for Halo2 proof generation
+    let api_prove = warp::path("api")
+        .and(warp::path("prove"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_keys.clone())
+        .and(with_canvas.clone())
+        .and_then(handle_prove);
+
+    // API route for proof verification against the cached verifying key
+    let api_verify = warp::path("api")
+        .and(warp::path("verify"))
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_keys)
+        .and(with_canvas)
+        .and_then(handle_verify);
+
+    // Serve static files
+    let static_files = warp::fs::dir(config.static_dir.clone());
+
+    // Health check endpoint
+    let health = warp::path("health")
+        .map(|| "🌌 zkBg OPTIMIZED Galaxy Server Running!");
+
+    api_generate
+        .or(api_gas_simulate)
+        .or(api_gas)
+        .or(api_svg_export)
+        .or(api_png_export)
+        .or(api_calldata)
+        .or(api_prove)
+        .or(api_verify)
+        .or(static_files)
+        .or(health)
+        .with(cors(&config.allowed_origins))
+}
+
+// "*" allows any origin; otherwise only the listed ones (validated at startup)
+fn cors(allowed_origins: &[String]) -> warp::cors::Builder {
+    let cors = warp::cors()
+        .allow_headers(vec!["content-type", "authorization"])
+        .allow_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"]);
+    if allowed_origins.iter().any(|origin| origin == "*") {
+        cors.allow_any_origin()
+    } else {
+        cors.allow_origins(allowed_origins.iter().map(String::as_str))
+    }
+}
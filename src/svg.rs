@@ -0,0 +1,221 @@
+// Small typed SVG builder: elements with escaped attributes/text, a document-level <defs>,
+// and IDs namespaced per galaxy so several can be inlined in one HTML page.
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+// Covers both attribute values (always double-quoted) and text content
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ID prefix for everything one galaxy defines; the same seed at two sizes can share a page
+pub fn galaxy_namespace(seed: u64, canvas_size: u64) -> String {
+    format!("zkbg-{}-{}", seed, canvas_size)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    name: &'static str,
+    attributes: Vec<(&'static str, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    pub fn new(name: &'static str) -> Self {
+        Element { name, attributes: Vec::new(), children: Vec::new() }
+    }
+
+    // Attributes are written in the order they are added
+    pub fn attr(mut self, name: &'static str, value: impl Display) -> Self {
+        self.attributes.push((name, value.to_string()));
+        self
+    }
+
+    pub fn child(mut self, child: Element) -> Self {
+        self.children.push(Node::Element(child));
+        self
+    }
+
+    pub fn children(mut self, children: impl IntoIterator<Item = Element>) -> Self {
+        self.children.extend(children.into_iter().map(Node::Element));
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.children.push(Node::Text(text.into()));
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn get_attr(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+// Childless elements self-close
+impl Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}", self.name)?;
+        for (name, value) in &self.attributes {
+            write!(f, " {}=\"{}\"", name, escape(value))?;
+        }
+        if self.children.is_empty() {
+            return write!(f, "/>");
+        }
+        write!(f, ">")?;
+        for child in &self.children {
+            match child {
+                Node::Element(element) => write!(f, "{}", element)?,
+                Node::Text(text) => write!(f, "{}", escape(text))?,
+            }
+        }
+        write!(f, "</{}>", self.name)
+    }
+}
+
+pub fn group() -> Element {
+    Element::new("g")
+}
+
+pub fn rect(width: u64, height: u64) -> Element {
+    Element::new("rect").attr("width", width).attr("height", height)
+}
+
+pub fn circle(cx: impl Display, cy: impl Display, r: impl Display) -> Element {
+    Element::new("circle").attr("cx", cx).attr("cy", cy).attr("r", r)
+}
+
+pub fn line(x1: impl Display, y1: impl Display, x2: impl Display, y2: impl Display) -> Element {
+    Element::new("line").attr("x1", x1).attr("y1", y1).attr("x2", x2).attr("y2", y2)
+}
+
+pub fn path(d: impl Display) -> Element {
+    Element::new("path").attr("d", d)
+}
+
+pub fn polygon(points: &[(u64, u64)]) -> Element {
+    let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+    Element::new("polygon").attr("points", points.join(" "))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientKind {
+    // Centred on the shape
+    Radial,
+    // Top-left to bottom-right
+    Linear,
+}
+
+// Stops as (offset %, color)
+pub fn gradient(kind: GradientKind, id: &str, stops: &[(u8, &str)]) -> Element {
+    let element = match kind {
+        GradientKind::Radial => Element::new("radialGradient").attr("id", id),
+        GradientKind::Linear => Element::new("linearGradient")
+            .attr("id", id)
+            .attr("x1", "0%")
+            .attr("y1", "0%")
+            .attr("x2", "100%")
+            .attr("y2", "100%"),
+    };
+    element.children(stops.iter().map(|(offset, color)| {
+        Element::new("stop").attr("offset", format!("{}%", offset)).attr("stop-color", color)
+    }))
+}
+
+// url(#id) reference for fill/stroke
+pub fn url(id: &str) -> String {
+    format!("url(#{})", id)
+}
+
+#[derive(Debug, Clone)]
+pub struct SvgDocument {
+    width: u64,
+    height: u64,
+    namespace: String,
+    defined_ids: HashSet<String>,
+    defs: Vec<Element>,
+    body: Vec<Element>,
+}
+
+impl SvgDocument {
+    pub fn new(width: u64, height: u64, namespace: impl Into<String>) -> Self {
+        SvgDocument {
+            width,
+            height,
+            namespace: namespace.into(),
+            defined_ids: HashSet::new(),
+            defs: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    // Namespaced ID for a document-local name, e.g. "bg" -> "zkbg-12345-420-bg"
+    pub fn id(&self, local: &str) -> String {
+        format!("{}-{}", self.namespace, local)
+    }
+
+    // Add to <defs>. An id that's already defined keeps its first definition and the
+    // new element is dropped (returns false), so the markup never has duplicate ids.
+    pub fn define(&mut self, element: Element) -> bool {
+        if let Some(id) = element.get_attr("id") {
+            if !self.defined_ids.insert(id.to_string()) {
+                return false;
+            }
+        }
+        self.defs.push(element);
+        true
+    }
+
+    pub fn push(&mut self, element: Element) {
+        self.body.push(element);
+    }
+
+    // <defs> (if any) followed by the body, without the <svg> wrapper
+    pub fn render_inner(&self) -> String {
+        let mut out = String::new();
+        if !self.defs.is_empty() {
+            out.push_str(&Element::new("defs").children(self.defs.iter().cloned()).to_string());
+        }
+        for element in &self.body {
+            out.push_str(&element.to_string());
+        }
+        out
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "<svg width=\"{}\" height=\"{}\" xmlns=\"{}\">{}</svg>",
+            self.width, self.height, SVG_NAMESPACE, self.render_inner()
+        )
+    }
+}